@@ -1,14 +1,14 @@
-use std::fs;
+use crate::{error::ParseError, Solution};
 
 pub struct Data {}
 
-impl Data {
-    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
-        let input = fs::read_to_string(path)?;
-        Self::parse(&input)
-    }
+impl Solution for Data {
+    const DAY: u8 = 0;
+
+    type Answer1 = u64;
+    type Answer2 = u64;
 
-    pub fn parse(input: &str) -> anyhow::Result<Self> {
+    fn parse(input: &str) -> Result<Self, ParseError> {
         for (line_num, line) in
             input.lines().enumerate().map(|(i, l)| (i + 1, l))
         {
@@ -17,11 +17,11 @@ impl Data {
         Ok(Self {})
     }
 
-    pub fn solve1(&self) -> anyhow::Result<u64> {
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
         todo!();
     }
 
-    pub fn solve2(&self) -> anyhow::Result<u64> {
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
         todo!();
     }
 }
@@ -34,7 +34,7 @@ mod tests {
     fn test() {
         let input = "";
         let data = Data::parse(input).unwrap();
-        data.solve1().unwrap();
-        data.solve2().unwrap();
+        data.part1().unwrap();
+        data.part2().unwrap();
     }
 }
@@ -0,0 +1,241 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DayEntry, Solution};
+
+/// Wall-clock durations of each phase of a single day's run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timings {
+    #[serde(rename = "parse_ns", with = "nanos")]
+    pub parse: Duration,
+    #[serde(rename = "part1_ns", with = "nanos")]
+    pub part1: Duration,
+    #[serde(rename = "part2_ns", with = "nanos")]
+    pub part2: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part1 + self.part2
+    }
+
+    /// Field-wise minimum, to filter out scheduling noise across runs.
+    fn min(self, other: Self) -> Self {
+        Self {
+            parse: self.parse.min(other.parse),
+            part1: self.part1.min(other.part1),
+            part2: self.part2.min(other.part2),
+        }
+    }
+}
+
+/// Timings keyed by day name, i.e. `DayEntry::name`.
+pub type Baseline = BTreeMap<String, Timings>;
+
+pub fn time<S: Solution>(input: &str) -> anyhow::Result<Timings> {
+    let t0 = Instant::now();
+    let data = S::parse(input)?;
+    let parse = t0.elapsed();
+
+    let t0 = Instant::now();
+    let _ = data.part1()?;
+    let part1 = t0.elapsed();
+
+    let t0 = Instant::now();
+    let _ = data.part2()?;
+    let part2 = t0.elapsed();
+
+    Ok(Timings {
+        parse,
+        part1,
+        part2,
+    })
+}
+
+/// Best of `runs` timings of the day on its input file.
+pub fn run(entry: &DayEntry, runs: usize) -> anyhow::Result<Timings> {
+    let path = entry.input_path();
+    let input = crate::read_input_file(&path)?;
+    let mut best = (entry.time)(&input)?;
+    for _ in 1..runs {
+        best = best.min((entry.time)(&input)?);
+    }
+    Ok(best)
+}
+
+pub fn load(path: &Path) -> anyhow::Result<Baseline> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        anyhow::anyhow!("Failure to open baseline file {:?}: {:?}", path, e)
+    })?;
+    let baseline = serde_json::from_str(&data)?;
+    Ok(baseline)
+}
+
+pub fn save(path: &Path, baseline: &Baseline) -> anyhow::Result<()> {
+    let data = serde_json::to_string_pretty(baseline)?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+/// Relative change of each phase, compared to a previous run.
+#[derive(Debug, Clone, Copy)]
+pub struct Delta {
+    pub parse: f64,
+    pub part1: f64,
+    pub part2: f64,
+}
+
+impl Delta {
+    pub fn new(before: &Timings, after: &Timings) -> Self {
+        Self {
+            parse: relative(before.parse, after.parse),
+            part1: relative(before.part1, after.part1),
+            part2: relative(before.part2, after.part2),
+        }
+    }
+
+    /// Is any phase slower by more than `threshold` (e.g. `0.1` = 10%)?
+    pub fn is_regression(&self, threshold: f64) -> bool {
+        [self.parse, self.part1, self.part2]
+            .iter()
+            .any(|d| *d > threshold)
+    }
+}
+
+fn relative(before: Duration, after: Duration) -> f64 {
+    let before = before.as_secs_f64();
+    let after = after.as_secs_f64();
+    if before > 0.0 {
+        (after - before) / before
+    } else {
+        0.0
+    }
+}
+
+impl fmt::Display for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>+9.1}% {:>+9.1}% {:>+9.1}%",
+            self.parse * 100.0,
+            self.part1 * 100.0,
+            self.part2 * 100.0
+        )
+    }
+}
+
+/// Fixed-width table row.
+pub fn row(name: &str, t: &Timings) -> String {
+    format!(
+        "{name:<8} {:>10} {:>10} {:>10} {:>10}",
+        fmt_duration(t.parse),
+        fmt_duration(t.part1),
+        fmt_duration(t.part2),
+        fmt_duration(t.total()),
+    )
+}
+
+pub fn header() -> String {
+    format!(
+        "{:<8} {:>10} {:>10} {:>10} {:>10}",
+        "day", "parse", "part 1", "part 2", "total"
+    )
+}
+
+fn fmt_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+    if nanos < 1_000 {
+        format!("{nanos}ns")
+    } else if nanos < 1_000_000 {
+        format!("{:.1}µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.1}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    }
+}
+
+mod nanos {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        d: &Duration,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_nanos() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Duration, D::Error> {
+        Ok(Duration::from_nanos(u64::deserialize(d)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(parse: u64, part1: u64, part2: u64) -> Timings {
+        Timings {
+            parse: Duration::from_nanos(parse),
+            part1: Duration::from_nanos(part1),
+            part2: Duration::from_nanos(part2),
+        }
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let baseline = Baseline::from([
+            ("dies_01".to_string(), timings(1, 22, 333)),
+            ("dies_02".to_string(), timings(4_000, 0, u32::MAX as u64)),
+        ]);
+        let json = serde_json::to_value(&baseline).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "parse_ns": 1,
+                "part1_ns": 22,
+                "part2_ns": 333,
+            }),
+            json["dies_01"]
+        );
+
+        let path = std::env::temp_dir()
+            .join(format!("acm-baseline-{}.json", std::process::id()));
+        save(&path, &baseline).unwrap();
+        let loaded = load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(baseline, loaded.unwrap());
+    }
+
+    #[test]
+    fn regression_threshold() {
+        // Chosen so that +25% comes out exact in floating point:
+        let ms = Duration::from_millis;
+        let timings = |part2| Timings {
+            parse: ms(512),
+            part1: ms(256),
+            part2,
+        };
+        let before = timings(ms(1_024));
+        let slower = |part2| Delta::new(&before, &timings(part2));
+        // Strictly more than the threshold is a regression:
+        assert!(!slower(ms(1_280)).is_regression(0.25));
+        assert!(slower(ms(1_281)).is_regression(0.25));
+        // Faster is never one, even with a zero threshold:
+        assert!(!slower(ms(1_000)).is_regression(0.0));
+        assert!(slower(ms(1_025)).is_regression(0.0));
+        // Nor is any change from a phase timed at zero:
+        let delta = Delta::new(&timings(Duration::ZERO), &before);
+        assert_eq!(0.0, delta.part2);
+        assert!(!delta.is_regression(0.0));
+    }
+}
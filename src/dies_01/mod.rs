@@ -0,0 +1,84 @@
+mod stream;
+
+use std::{collections::HashMap, iter};
+
+pub use stream::Streaming;
+
+use crate::{
+    error::{self, ParseError, Reason},
+    Solution,
+};
+
+pub struct Data {
+    left: Vec<i32>,
+    right: Vec<i32>,
+}
+
+impl Solution for Data {
+    const DAY: u8 = 1;
+
+    type Answer1 = u32;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for (line_number, line) in
+            input.lines().enumerate().map(|(i, l)| (i + 1, l))
+        {
+            let (l, r) = parse_line(line_number, line)?;
+            left.push(l);
+            right.push(r);
+        }
+        Ok(Self { left, right })
+    }
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
+        let mut left = self.left.clone();
+        let mut right = self.right.clone();
+        left.sort();
+        right.sort();
+        let total_distance: u32 =
+            iter::zip(left, right).map(|(l, r)| l.abs_diff(r)).sum();
+        Ok(total_distance)
+    }
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
+        let mut right_hist = HashMap::new();
+        for n in &self.right {
+            right_hist
+                .entry(*n)
+                .and_modify(|count| *count += 1)
+                .or_insert(1);
+        }
+        let similarity_score: i32 = self
+            .left
+            .iter()
+            .map(|n| *n * *right_hist.get(n).unwrap_or(&0))
+            .sum();
+        Ok(similarity_score)
+    }
+}
+
+/// The two location IDs on a line.
+fn parse_line(
+    line_number: usize,
+    line: &str,
+) -> Result<(i32, i32), ParseError> {
+    let parse = |field| error::parse_num(Data::DAY, line_number, line, field);
+    let mut fields = line.split_whitespace();
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(n_left), Some(n_right), None) => {
+            Ok((parse(n_left)?, parse(n_right)?))
+        }
+        (_, _, extra) => {
+            let reason = Reason::FieldCount {
+                expected: 2,
+                found: line.split_whitespace().count(),
+            };
+            let column =
+                extra.map_or(line.len() + 1, |f| error::column(line, f));
+            Err(ParseError::new(Data::DAY, line_number, column, reason))
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    iter::Peekable,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::anyhow;
+
+use super::parse_line;
+
+/// Both parts for location lists too long to hold in memory, read line by
+/// line from any reader.
+///
+/// Both sort each list externally: in sorted runs of up to `chunk_len`
+/// IDs, written to a temporary directory, then merged at most `fan_in`
+/// runs at a time. The distance then pairs up the sorted lists, and the
+/// similarity joins them, counting the times of each ID in both as they
+/// go by.
+#[derive(Debug, Clone)]
+pub struct Streaming {
+    chunk_len: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+}
+
+impl Default for Streaming {
+    fn default() -> Self {
+        Self {
+            chunk_len: 1 << 20,
+            fan_in: 64,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+}
+
+impl Streaming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Most IDs of each list held in memory at once, at least 1.
+    pub fn with_chunk_len(self, chunk_len: usize) -> Self {
+        Self {
+            chunk_len: chunk_len.max(1),
+            ..self
+        }
+    }
+
+    /// Most runs merged at once, at least 2.
+    pub fn with_fan_in(self, fan_in: usize) -> Self {
+        Self {
+            fan_in: fan_in.max(2),
+            ..self
+        }
+    }
+
+    /// Where to make the directory for sorted runs, which is removed when
+    /// done.
+    pub fn with_temp_dir(self, temp_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            temp_dir: temp_dir.into(),
+            ..self
+        }
+    }
+
+    /// Part 1: total distance between the lists, once both are sorted.
+    pub fn distance(&self, reader: impl BufRead) -> anyhow::Result<u64> {
+        self.sort(reader)?.distance()
+    }
+
+    /// Part 2: similarity score, the sum of each left ID times the times
+    /// it is in the right list.
+    pub fn similarity(&self, reader: impl BufRead) -> anyhow::Result<i64> {
+        self.sort(reader)?.similarity()
+    }
+
+    /// Both parts, reading and sorting the lists only once.
+    pub fn both(&self, reader: impl BufRead) -> anyhow::Result<(u64, i64)> {
+        let sorted = self.sort(reader)?;
+        Ok((sorted.distance()?, sorted.similarity()?))
+    }
+
+    fn sort(&self, reader: impl BufRead) -> anyhow::Result<Sorted> {
+        let mut dir = TempDir::new(&self.temp_dir)?;
+        let mut left = Sorter::new(self.chunk_len);
+        let mut right = Sorter::new(self.chunk_len);
+        for_each_pair(reader, |l, r| {
+            left.push(l, &mut dir)?;
+            right.push(r, &mut dir)?;
+            Ok(())
+        })?;
+        Ok(Sorted {
+            left: left.finish(self.fan_in, &mut dir)?,
+            right: right.finish(self.fan_in, &mut dir)?,
+            _dir: dir,
+        })
+    }
+}
+
+/// Runs of both lists, each merged down to at most `fan_in`, ready to be
+/// read in order as many times as needed.
+struct Sorted {
+    left: Vec<PathBuf>,
+    right: Vec<PathBuf>,
+    /// Removes the runs along with the rest.
+    _dir: TempDir,
+}
+
+impl Sorted {
+    fn distance(&self) -> anyhow::Result<u64> {
+        let left = Merge::open(&self.left)?;
+        let right = Merge::open(&self.right)?;
+        let mut total: u64 = 0;
+        for (l, r) in left.zip(right) {
+            total = total
+                .checked_add(u64::from(l?.abs_diff(r?)))
+                .ok_or_else(|| anyhow!("Total distance overflows."))?;
+        }
+        Ok(total)
+    }
+
+    fn similarity(&self) -> anyhow::Result<i64> {
+        let left = Counted(Merge::open(&self.left)?.peekable());
+        let mut right =
+            Counted(Merge::open(&self.right)?.peekable()).peekable();
+        let mut total: i64 = 0;
+        for counted in left {
+            let (id, times_left) = counted?;
+            // Skipping right IDs not in the left list, and stopping before
+            // any errors, so as to return them:
+            let mut times_right = 0;
+            while let Some(counted) =
+                right.next_if(|next| !matches!(next, Ok((n, _)) if *n > id))
+            {
+                let (n, times) = counted?;
+                if n == id {
+                    times_right = times;
+                }
+            }
+            total = i64::from(id)
+                .checked_mul(times_left)
+                .and_then(|n| n.checked_mul(times_right))
+                .and_then(|n| n.checked_add(total))
+                .ok_or_else(|| anyhow!("Similarity score overflows."))?;
+        }
+        Ok(total)
+    }
+}
+
+/// Calls `f` with the IDs on each line, reusing one line buffer.
+fn for_each_pair(
+    mut reader: impl BufRead,
+    mut f: impl FnMut(i32, i32) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        line_number += 1;
+        let line = line.trim_end_matches(['\n', '\r']);
+        let (l, r) = parse_line(line_number, line)?;
+        f(l, r)?;
+    }
+}
+
+/// Directory of numbered files, removed along with them when dropped.
+struct TempDir {
+    path: PathBuf,
+    files: usize,
+}
+
+impl TempDir {
+    fn new(parent: &Path) -> io::Result<Self> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "acm-dies_01-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = parent.join(name);
+        fs::create_dir(&path)?;
+        Ok(Self { path, files: 0 })
+    }
+
+    fn next_file(&mut self) -> PathBuf {
+        self.files += 1;
+        self.path.join(format!("{}.run", self.files))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// One list, sorted externally, see [`Streaming`].
+struct Sorter {
+    chunk: Vec<i32>,
+    chunk_len: usize,
+    runs: Vec<PathBuf>,
+}
+
+impl Sorter {
+    fn new(chunk_len: usize) -> Self {
+        Self {
+            chunk: Vec::with_capacity(chunk_len),
+            chunk_len,
+            runs: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, id: i32, dir: &mut TempDir) -> io::Result<()> {
+        self.chunk.push(id);
+        if self.chunk.len() == self.chunk_len {
+            self.flush(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Writes out the chunk as a sorted run.
+    fn flush(&mut self, dir: &mut TempDir) -> io::Result<()> {
+        self.chunk.sort_unstable();
+        let path = dir.next_file();
+        write_run(&path, self.chunk.drain(..).map(Ok))?;
+        self.runs.push(path);
+        Ok(())
+    }
+
+    /// Runs of every ID, after merging them down to `fan_in`.
+    fn finish(
+        mut self,
+        fan_in: usize,
+        dir: &mut TempDir,
+    ) -> io::Result<Vec<PathBuf>> {
+        if !self.chunk.is_empty() {
+            self.flush(dir)?;
+        }
+        let mut runs = self.runs;
+        while runs.len() > fan_in {
+            let mut merged = Vec::with_capacity(runs.len().div_ceil(fan_in));
+            for group in runs.chunks(fan_in) {
+                let path = dir.next_file();
+                write_run(&path, Merge::open(group)?)?;
+                merged.push(path);
+                for run in group {
+                    fs::remove_file(run)?;
+                }
+            }
+            runs = merged;
+        }
+        Ok(runs)
+    }
+}
+
+fn write_run(
+    path: &Path,
+    ids: impl Iterator<Item = io::Result<i32>>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for id in ids {
+        writer.write_all(&id?.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// IDs of a run, as written by [`write_run`].
+struct Run(BufReader<File>);
+
+impl Iterator for Run {
+    type Item = io::Result<i32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = [0; 4];
+        match self.0.read_exact(&mut bytes) {
+            Ok(()) => Some(Ok(i32::from_le_bytes(bytes))),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// IDs of several sorted runs, in order.
+struct Merge {
+    runs: Vec<Run>,
+    /// Next ID of each run which has any left, with the run's index.
+    heads: BinaryHeap<Reverse<(i32, usize)>>,
+    error: Option<io::Error>,
+}
+
+impl Merge {
+    fn open(paths: &[PathBuf]) -> io::Result<Self> {
+        let mut merge = Self {
+            runs: Vec::with_capacity(paths.len()),
+            heads: BinaryHeap::with_capacity(paths.len()),
+            error: None,
+        };
+        for path in paths {
+            merge.runs.push(Run(BufReader::new(File::open(path)?)));
+            merge.advance(merge.runs.len() - 1)?;
+        }
+        Ok(merge)
+    }
+
+    /// Queues the next ID of run `i`, if any.
+    fn advance(&mut self, i: usize) -> io::Result<()> {
+        if let Some(id) = self.runs[i].next().transpose()? {
+            self.heads.push(Reverse((id, i)));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Merge {
+    type Item = io::Result<i32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let Reverse((id, i)) = self.heads.pop()?;
+        if let Err(e) = self.advance(i) {
+            // Reported by the next call, after this ID:
+            self.error = Some(e);
+        }
+        Some(Ok(id))
+    }
+}
+
+/// Each distinct ID of a sorted list, with the times it is there.
+struct Counted<I: Iterator>(Peekable<I>);
+
+impl<I: Iterator<Item = io::Result<i32>>> Iterator for Counted<I> {
+    type Item = io::Result<(i32, i64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = match self.0.next()? {
+            Ok(id) => id,
+            Err(e) => return Some(Err(e)),
+        };
+        let mut times = 1;
+        while self
+            .0
+            .next_if(|next| matches!(next, Ok(n) if *n == id))
+            .is_some()
+        {
+            times += 1;
+        }
+        Some(Ok((id, times)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dies_01::Data, Solution};
+
+    /// Lines of pseudo-random IDs, with plenty of repeats.
+    fn lines(n: usize) -> String {
+        let mut x: u64 = 12345;
+        let mut next = || {
+            x = x.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (x >> 33) % 500
+        };
+        (0..n)
+            .map(|_| format!("{}   {}\n", next(), next()))
+            .collect()
+    }
+
+    #[test]
+    fn agrees_with_data() {
+        let dir = std::env::temp_dir()
+            .join(format!("acm-dies_01-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for n in [0, 1, 6, 1000] {
+            let input = lines(n);
+            let data = Data::parse(&input).unwrap();
+            for (chunk_len, fan_in) in [(1, 2), (7, 3), (1 << 20, 64)] {
+                let streaming = Streaming::new()
+                    .with_chunk_len(chunk_len)
+                    .with_fan_in(fan_in)
+                    .with_temp_dir(&dir);
+                let distance = streaming.distance(input.as_bytes()).unwrap();
+                assert_eq!(u64::from(data.part1().unwrap()), distance);
+                let similarity =
+                    streaming.similarity(input.as_bytes()).unwrap();
+                assert_eq!(i64::from(data.part2().unwrap()), similarity);
+                let both = streaming.both(input.as_bytes()).unwrap();
+                assert_eq!((distance, similarity), both);
+            }
+        }
+        // Every run and temporary directory is gone:
+        assert_eq!(0, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let streaming = Streaming::new();
+        let e = streaming.distance("1 2\r\n3 4 5\n".as_bytes()).unwrap_err();
+        let e = e.downcast::<crate::error::ParseError>().unwrap();
+        assert_eq!((2, 5), (e.line, e.column));
+        // 70,000 squared times the ID is too big:
+        let input = format!("{0} {0}\n", i32::MAX).repeat(70_000);
+        assert!(streaming.similarity(input.as_bytes()).is_err());
+        let missing = Streaming::new().with_temp_dir("no/such/dir");
+        assert!(missing.distance("1 2\n".as_bytes()).is_err());
+    }
+}
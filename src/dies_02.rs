@@ -1,44 +1,41 @@
-use std::{io::BufRead, iter::zip};
+use std::iter::zip;
 
-use anyhow::anyhow;
+use crate::{
+    error::{self, ParseError, Reason},
+    Solution,
+};
 
 pub struct Data {
     reports: Vec<Vec<i32>>,
 }
 
-impl Data {
-    pub fn load(input: &std::path::Path) -> anyhow::Result<Self> {
-        let file = std::fs::File::open(input).map_err(|e| {
-            anyhow!("Failure to open input file {:?}: {:?}", input, e)
-        })?;
+impl Solution for Data {
+    const DAY: u8 = 2;
+
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
         let mut reports = Vec::new();
-        for (line_num, line_result) in std::io::BufReader::new(file)
-            .lines()
-            .enumerate()
-            .map(|(i, l)| (i + 1, l))
+        for (line_num, line) in
+            input.lines().enumerate().map(|(i, l)| (i + 1, l))
         {
-            let line = line_result?;
             let report: Vec<i32> = line
                 .split_whitespace()
-                .filter_map(|level| {
-                    level
-                        .parse()
-                        .inspect_err(|e| {
-                            eprintln!(
-                                "Failed to parse level={level:?} \
-                                on line={line_num} \
-                                with error={e:?}"
-                            );
-                        })
-                        .ok()
+                .map(|level| {
+                    error::parse_num(Self::DAY, line_num, line, level)
                 })
-                .collect();
+                .collect::<Result<_, _>>()?;
+            if report.is_empty() {
+                let reason = Reason::Missing("levels");
+                return Err(ParseError::new(Self::DAY, line_num, 1, reason));
+            }
             reports.push(report);
         }
         Ok(Self { reports })
     }
 
-    pub fn solve1(&self) -> anyhow::Result<usize> {
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
         let mut safe_count: usize = 0;
         for report in &self.reports {
             if is_safe(&report[..]) {
@@ -48,7 +45,7 @@ impl Data {
         Ok(safe_count)
     }
 
-    pub fn solve2(&self) -> anyhow::Result<usize> {
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
         let mut safe_count: usize = 0;
         'reports: for report in &self.reports {
             let n = report.len();
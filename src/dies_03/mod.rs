@@ -1,16 +1,76 @@
 mod parser;
+mod stream;
+mod vm;
 
-#[derive(Debug, PartialEq)]
+use std::io::BufRead;
+
+pub use parser::{
+    diagnose, parse_spanned, Diagnostic, Rejection, Spanned, Syntax,
+    MAX_DEPTH, MAX_DIGITS,
+};
+pub use stream::{stream, Stream};
+pub use vm::{Config, Machine};
+
+use crate::{error::ParseError, Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    /// Integer division, rounding towards zero.
+    Div,
+}
+
+/// One of the 26 registers, `a` to `z`, each starting at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reg(u8);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Num(i64),
+    Reg(Reg),
+    Call(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Ix {
     Do,
     Dont,
-    Mul(i32, i32),
+    /// Evaluates a call, e.g. `mul(add(1,2),x)`, adding its result to the
+    /// accumulator.
+    Eval(Expr),
+    /// Evaluates an operand into a register, e.g. `set(x,mul(2,3))`.
+    Set(Reg, Expr),
 }
 
 pub struct Data {
     ixs: Vec<Ix>,
 }
 
+impl Reg {
+    pub fn new(name: char) -> Option<Self> {
+        name.is_ascii_lowercase().then(|| Self(name as u8 - b'a'))
+    }
+
+    pub fn index(self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+impl Expr {
+    pub fn call(op: Op, left: Expr, right: Expr) -> Self {
+        Self::Call(op, Box::new(left), Box::new(right))
+    }
+}
+
+impl Ix {
+    /// `mul(left,right)`, the puzzle's only arithmetic.
+    pub fn mul(left: i64, right: i64) -> Self {
+        Self::Eval(Expr::call(Op::Mul, Expr::Num(left), Expr::Num(right)))
+    }
+}
+
 impl From<Vec<Ix>> for Data {
     fn from(ixs: Vec<Ix>) -> Self {
         Self { ixs }
@@ -18,85 +78,125 @@ impl From<Vec<Ix>> for Data {
 }
 
 impl Data {
-    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
-        let input = std::fs::read_to_string(path)?;
-        Self::parse(&input)
+    /// Instructions written in the given syntax, see [`Syntax`].
+    pub fn parse_with(input: &str, syntax: Syntax) -> Self {
+        Self::from(parser::parse(input, syntax))
     }
 
-    pub fn parse(input: &str) -> anyhow::Result<Self> {
-        let ixs = parser::parse(input);
-        Ok(Self::from(ixs))
+    pub fn ixs(&self) -> &[Ix] {
+        &self.ixs[..]
     }
 
-    pub fn solve1(&self) -> anyhow::Result<i32> {
-        let mut sum_of_products = 0;
-        for ix in self.ixs.iter() {
-            match ix {
-                Ix::Do | Ix::Dont => (),
-                Ix::Mul(left, right) => {
-                    sum_of_products += *left * *right;
-                }
-            }
+    /// Runs a program straight from a reader on a fresh machine, one
+    /// instruction at a time, in constant memory.
+    pub fn run_reader(
+        reader: impl BufRead,
+        syntax: Syntax,
+        config: Config,
+    ) -> anyhow::Result<Machine> {
+        let mut machine = Machine::new(config);
+        for spanned in stream(reader, syntax) {
+            machine.step(&spanned?.ix)?;
         }
-        Ok(sum_of_products)
-    }
-
-    pub fn solve2(&self) -> anyhow::Result<i32> {
-        let mut sum_of_products = 0;
-        let mut enabled = true;
-        for ix in self.ixs.iter() {
-            match ix {
-                Ix::Do => enabled = true,
-                Ix::Dont => enabled = false,
-                Ix::Mul(left, right) if enabled => {
-                    sum_of_products += *left * *right;
-                }
-                Ix::Mul(_, _) => {}
-            }
-        }
-        Ok(sum_of_products)
+        Ok(machine)
+    }
+
+    /// Runs the whole program on a fresh machine.
+    pub fn run(&self, config: Config) -> anyhow::Result<Machine> {
+        let mut machine = Machine::new(config);
+        machine.run(&self.ixs)?;
+        Ok(machine)
+    }
+}
+
+impl Solution for Data {
+    const DAY: u8 = 3;
+
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self::parse_with(input, Syntax::Classic))
+    }
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
+        Ok(self.run(Config::PART_1)?.acc)
+    }
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
+        Ok(self.run(Config::PART_2)?.acc)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{fs::File, io::BufReader, path::Path};
+
     use super::*;
 
     #[test]
     fn example_part_1() {
         let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
         let data = Data::parse(input).unwrap();
-        assert_eq!(161, data.solve1().unwrap());
+        assert_eq!(161, data.part1().unwrap());
     }
 
     #[test]
     fn example_part_2() {
         let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         let data = Data::parse(input).unwrap();
-        assert_eq!(48, data.solve2().unwrap());
+        assert_eq!(48, data.part2().unwrap());
     }
 
     #[test]
     fn interp_part_1() {
         let data = Data::from(vec![
-            Ix::Mul(2, 4),
-            Ix::Mul(5, 5),
-            Ix::Mul(11, 8),
-            Ix::Mul(8, 5),
+            Ix::mul(2, 4),
+            Ix::mul(5, 5),
+            Ix::mul(11, 8),
+            Ix::mul(8, 5),
         ]);
-        assert_eq!(161, data.solve2().unwrap())
+        assert_eq!(161, data.part2().unwrap())
     }
 
     #[test]
     fn interp_part_2() {
         let data = Data::from(vec![
-            Ix::Mul(2, 4),
+            Ix::mul(2, 4),
             Ix::Dont,
-            Ix::Mul(5, 5),
-            Ix::Mul(11, 8),
+            Ix::mul(5, 5),
+            Ix::mul(11, 8),
             Ix::Do,
-            Ix::Mul(8, 5),
+            Ix::mul(8, 5),
         ]);
-        assert_eq!(48, data.solve2().unwrap())
+        assert_eq!(48, data.part2().unwrap())
+    }
+
+    #[test]
+    fn streamed() {
+        let path = Path::new("tests/input/dies_03/example_2.txt");
+        assert_eq!(48, Data::load(path).unwrap().part2().unwrap());
+        let input = File::open(path).map(BufReader::new).unwrap();
+        let machine =
+            Data::run_reader(input, Syntax::Classic, Config::PART_1).unwrap();
+        assert_eq!(2 * 4 + 5 * 5 + 11 * 8 + 8 * 5, machine.acc);
+    }
+
+    #[test]
+    fn extended() {
+        let input = "set(x,add(1,2))%mul(x,4)don't()set(y,9)do()\
+            div(sub(y,x),mul(2,1))#mul(add(1,2),3]add(y,y)";
+        let data = Data::parse_with(input, Syntax::Extended);
+        // The broken `mul` still leaves the `add` nested in it:
+        assert_eq!(8, data.ixs().len());
+        let machine = data.run(Config::PART_2).unwrap();
+        // 3 * 4 + (0 - 3) / 2 + 3 + 0, since set(y,9) was disabled:
+        assert_eq!(12 - 1 + 3, machine.acc);
+        assert_eq!(3, machine.registers[Reg::new('x').unwrap().index()]);
+        let machine = data.run(Config::PART_1).unwrap();
+        assert_eq!(12 + 3 + 3 + 18, machine.acc);
+        // The classic syntax only knows `mul` of two numbers:
+        let data = Data::parse_with(input, Syntax::Classic);
+        assert_eq!(vec![Ix::Dont, Ix::Do, Ix::mul(2, 1)], data.ixs());
     }
 }
@@ -1,146 +1,695 @@
-use std::{iter::Peekable, str::Chars};
+use std::{collections::VecDeque, fmt, io, ops::Range};
 
-use super::Ix;
+use super::{Expr, Ix, Op, Reg};
 
-pub fn parse(input: &str) -> Vec<Ix> {
-    let mut ixs = Vec::new();
-    let mut chars = input.chars().peekable();
-    while chars.peek().is_some() {
-        let ix_opt = parse_ix(&mut chars);
-        if let Some(ix) = ix_opt {
-            ixs.push(ix);
-        }
-    }
-    ixs
+/// Which instructions to look for in the corrupted memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Syntax {
+    /// `do()`, `don't()` and `mul` of two numbers, as in the puzzle.
+    /// Numbers, in either syntax, have one to three digits.
+    #[default]
+    Classic,
+    /// Also `add`, `sub` and `div`, `set(r,x)` into a register, and
+    /// operands which are registers or nested calls.
+    Extended,
 }
 
-fn parse_ix(chars: &mut Peekable<Chars>) -> Option<Ix> {
-    consume_non_key(chars)?;
-    let key_candidate = parse_key_candidate(chars)?;
-    // Checking the ends, rather than wholes, makes it easier to
-    // ignore noise prefixes:
-    if key_candidate.ends_with("do") {
-        consume_unit(chars)?;
-        Some(Ix::Do)
-    } else if key_candidate.ends_with("don't") {
-        consume_unit(chars)?;
-        Some(Ix::Dont)
-    } else if key_candidate.ends_with("mul") {
-        consume_paren_open(chars)?;
-        let left = parse_num(chars)?;
-        consume_comma(chars)?;
-        let right = parse_num(chars)?;
-        consume_paren_close(chars)?;
-        Some(Ix::Mul(left, right))
-    } else {
-        None
-    }
+/// Instruction, with the byte range of its text in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spanned {
+    pub ix: Ix,
+    pub span: Range<usize>,
 }
-fn parse_num(chars: &mut Peekable<Chars>) -> Option<i32> {
-    let mut num = None;
-    let mut buf = String::new();
-    while let Some(c) = chars.peek() {
-        if c.is_ascii_digit() {
-            buf.push(*c);
-            chars.next().unwrap_or_else(|| unreachable!());
-        } else {
-            num = Some(buf.parse().unwrap_or_else(|_| unreachable!()));
-            break;
-        }
-    }
-    num
+
+/// Instruction name which was not followed by a well-formed instruction,
+/// e.g. `mul[3,7]`. The span runs from the name through the character
+/// where parsing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub span: Range<usize>,
+    pub reason: Rejection,
 }
 
-fn is_key_char(c: char) -> bool {
-    "don'tmul".contains(c)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// `found` is `None` at the end of the input.
+    Expected {
+        what: &'static str,
+        found: Option<char>,
+    },
+    /// More than [`MAX_DIGITS`] digits.
+    TooManyDigits(usize),
+    /// Calls nested more than [`MAX_DEPTH`] deep.
+    TooDeep,
 }
 
-fn parse_key_candidate(chars: &mut Peekable<Chars>) -> Option<String> {
-    let mut key = None;
-    let mut buf = String::new();
-    while let Some(c) = chars.peek() {
-        if is_key_char(*c) {
-            buf.push(*c);
-            chars.next().unwrap_or_else(|| unreachable!());
-        } else {
-            key = Some(buf.to_string());
-            break;
+/// Longest number an operand may have, in digits.
+pub const MAX_DIGITS: usize = 3;
+
+/// Most calls an instruction may have, one inside another, so that
+/// neither parsing nor evaluating it can overflow the stack.
+pub const MAX_DEPTH: usize = 64;
+
+/// Instruction names, the classic ones first.
+const KEYS: [&str; 7] = ["do", "don't", "mul", "add", "sub", "div", "set"];
+
+/// Length of the longest key, in bytes, which are all ASCII.
+const LONGEST_KEY: usize = 5;
+
+impl Syntax {
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Classic => &KEYS[..3],
+            Self::Extended => &KEYS[..],
         }
     }
-    key
+
+    /// The key which `window` ends with exactly, unless `next` goes on to
+    /// spell a longer key, as `n` does after `do`.
+    fn key(self, window: &str, next: Option<char>) -> Option<&'static str> {
+        let key = self.keys().iter().find(|k| window.ends_with(*k))?;
+        let longer = self.keys().iter().any(|k| {
+            k.strip_prefix(key)
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| Some(c) == next)
+        });
+        (!longer).then_some(key)
+    }
+
+    /// Whether some call name in this syntax starts with `prefix`.
+    fn starts_op(self, prefix: &str) -> bool {
+        self.keys()
+            .iter()
+            .any(|k| k.starts_with(prefix) && self.op(k).is_some())
+    }
+
+    fn op(self, key: &str) -> Option<Op> {
+        let op = match key {
+            "mul" => Op::Mul,
+            "add" => Op::Add,
+            "sub" => Op::Sub,
+            "div" => Op::Div,
+            _ => return None,
+        };
+        (self == Self::Extended || op == Op::Mul).then_some(op)
+    }
+}
+
+pub fn parse(input: &str, syntax: Syntax) -> Vec<Ix> {
+    parse_spanned(input, syntax)
+        .into_iter()
+        .map(|spanned| spanned.ix)
+        .collect()
 }
 
-fn consume_non_key(chars: &mut Peekable<Chars>) -> Option<()> {
-    while let Some(c) = chars.peek() {
-        if is_key_char(*c) {
-            return Some(());
-        } else {
-            chars.next().unwrap_or_else(|| unreachable!());
+pub fn parse_spanned(input: &str, syntax: Syntax) -> Vec<Spanned> {
+    diagnose(input, syntax).0
+}
+
+/// Instructions, along with every near miss.
+pub fn diagnose(
+    input: &str,
+    syntax: Syntax,
+) -> (Vec<Spanned>, Vec<Diagnostic>) {
+    let chars = input
+        .char_indices()
+        .map(|(i, c)| Ok((i..i + c.len_utf8(), c)));
+    let mut parser = Parser::new(chars, syntax);
+    let mut ixs = Vec::new();
+    let mut diagnostics = Vec::new();
+    while let Some(found) = parser.next_found() {
+        match found {
+            Ok(ix) => ixs.push(ix),
+            Err(diagnostic) => diagnostics.push(diagnostic),
         }
     }
-    None
+    (ixs, diagnostics)
 }
 
-fn consume_unit(chars: &mut Peekable<Chars>) -> Option<()> {
-    consume_paren_open(chars)?;
-    consume_paren_close(chars)?;
-    Some(())
+/// Where an instruction stopped parsing, and why.
+struct Failure {
+    span: Range<usize>,
+    reason: Rejection,
 }
 
-fn consume_paren_open(chars: &mut Peekable<Chars>) -> Option<()> {
-    consume_char(chars, '(')
+/// Parses characters, with their byte ranges, as they come from `source`,
+/// never holding more than a few of them. Stops at the first error from
+/// `source`, keeping it for [`Parser::take_error`].
+pub(super) struct Parser<S> {
+    source: S,
+    peeked: Option<(Range<usize>, char)>,
+    /// Byte offset right after the last character taken.
+    end: usize,
+    /// The last few ASCII characters taken, enough to end with any key.
+    /// Kept across instructions which fail, so a key can start in the
+    /// text they took.
+    window: String,
+    /// Calls nested in an instruction which failed, still to be returned
+    /// as instructions of their own.
+    salvaged: VecDeque<Spanned>,
+    /// Calls being parsed, one inside another.
+    depth: usize,
+    error: Option<io::Error>,
+    syntax: Syntax,
 }
 
-fn consume_paren_close(chars: &mut Peekable<Chars>) -> Option<()> {
-    consume_char(chars, ')')
+impl<S> Parser<S>
+where
+    S: Iterator<Item = io::Result<(Range<usize>, char)>>,
+{
+    pub(super) fn new(source: S, syntax: Syntax) -> Self {
+        Self {
+            source,
+            peeked: None,
+            end: 0,
+            window: String::with_capacity(LONGEST_KEY),
+            salvaged: VecDeque::new(),
+            depth: 0,
+            error: None,
+            syntax,
+        }
+    }
+
+    /// Next instruction, or near miss, until the end of the input.
+    pub(super) fn next_found(
+        &mut self,
+    ) -> Option<Result<Spanned, Diagnostic>> {
+        if let Some(ix) = self.salvaged.pop_front() {
+            return Some(Ok(ix));
+        }
+        while self.next_if(|_| true).is_some() {
+            let next = self.peek();
+            if let Some(key) = self.syntax.key(&self.window, next) {
+                if let Some(found) = self.parse_ix(key) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    pub(super) fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.peek_spanned().map(|(_, c)| c)
+    }
+
+    fn peek_spanned(&mut self) -> Option<(Range<usize>, char)> {
+        if self.peeked.is_none() && self.error.is_none() {
+            match self.source.next() {
+                Some(Ok(next)) => self.peeked = Some(next),
+                Some(Err(e)) => self.error = Some(e),
+                None => (),
+            }
+        }
+        self.peeked.clone()
+    }
+
+    fn next_if(&mut self, f: impl FnOnce(char) -> bool) -> Option<char> {
+        match self.peek_spanned() {
+            Some((span, c)) if f(c) => {
+                self.peeked = None;
+                self.end = span.end;
+                if !c.is_ascii() {
+                    self.window.clear();
+                } else {
+                    if self.window.len() == LONGEST_KEY {
+                        self.window.remove(0);
+                    }
+                    self.window.push(c);
+                }
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    /// Byte offset of the next character.
+    fn offset(&mut self) -> usize {
+        self.peek_spanned().map_or(self.end, |(span, _)| span.start)
+    }
+
+    /// The instruction named `key`, which was just taken.
+    fn parse_ix(&mut self, key: &str) -> Option<Result<Spanned, Diagnostic>> {
+        let start = self.offset() - key.len();
+        match self.parse_after_key(key) {
+            Ok(ix) => Some(Ok(Spanned {
+                ix: ix?,
+                span: start..self.offset(),
+            })),
+            Err(Failure { span, reason }) => Some(Err(Diagnostic {
+                span: start..span.end,
+                reason,
+            })),
+        }
+    }
+
+    /// The rest of the instruction named `key`, or `None` if there is no
+    /// such instruction in this syntax.
+    fn parse_after_key(&mut self, key: &str) -> Result<Option<Ix>, Failure> {
+        let ix = match key {
+            "do" => {
+                self.consume_unit()?;
+                Ix::Do
+            }
+            "don't" => {
+                self.consume_unit()?;
+                Ix::Dont
+            }
+            "set" if self.syntax == Syntax::Extended => {
+                self.consume_char('(')?;
+                let reg = self.parse_reg()?;
+                self.consume_char(',')?;
+                let mark = self.salvaged.len();
+                let expr = self.parse_spanned_operand()?;
+                if let Err(failure) = self.consume_char(')') {
+                    self.salvage(mark, expr);
+                    return Err(failure);
+                }
+                Ix::Set(reg, expr.0)
+            }
+            _ => match self.syntax.op(key) {
+                Some(op) => Ix::Eval(self.parse_call(op)?),
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(ix))
+    }
+
+    /// Parenthesized operands of a call, whose name was already parsed.
+    fn parse_call(&mut self, op: Op) -> Result<Expr, Failure> {
+        if self.depth == MAX_DEPTH {
+            return Err(Failure {
+                span: self.end..self.end,
+                reason: Rejection::TooDeep,
+            });
+        }
+        self.depth += 1;
+        let call = self.parse_operands(op);
+        self.depth -= 1;
+        call
+    }
+
+    fn parse_operands(&mut self, op: Op) -> Result<Expr, Failure> {
+        self.consume_char('(')?;
+        let mark = self.salvaged.len();
+        let left = self.parse_spanned_operand()?;
+        let right = match self
+            .consume_char(',')
+            .and_then(|()| self.parse_spanned_operand())
+        {
+            Ok(right) => right,
+            Err(failure) => {
+                self.salvage(mark, left);
+                return Err(failure);
+            }
+        };
+        if let Err(failure) = self.consume_char(')') {
+            self.salvage(mark, right);
+            self.salvage(mark, left);
+            return Err(failure);
+        }
+        Ok(Expr::call(op, left.0, right.0))
+    }
+
+    /// Keeps an operand which is a call, at `at` among the salvaged ones,
+    /// since the instruction around it failed: scanning its text again
+    /// would find the call as an instruction.
+    fn salvage(&mut self, at: usize, (expr, span): (Expr, Range<usize>)) {
+        if let Expr::Call(..) = expr {
+            let ix = Ix::Eval(expr);
+            self.salvaged.insert(at, Spanned { ix, span });
+        }
+    }
+
+    fn parse_spanned_operand(
+        &mut self,
+    ) -> Result<(Expr, Range<usize>), Failure> {
+        let start = self.offset();
+        let expr = self.parse_operand()?;
+        Ok((expr, start..self.offset()))
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, Failure> {
+        match (self.syntax, self.peek()) {
+            (_, Some(c)) if c.is_ascii_digit() => {
+                Ok(Expr::Num(self.parse_num()?))
+            }
+            (Syntax::Classic, _) => Err(self.expected("a number")),
+            (Syntax::Extended, _) => {
+                let start = self.offset();
+                // Only as far as a call name could go, leaving the rest,
+                // which may start an instruction, to the scanner:
+                let syntax = self.syntax;
+                let mut name = String::new();
+                while let Some(c) = self.next_if(|c| {
+                    c.is_ascii_lowercase()
+                        && (name.is_empty()
+                            || syntax.starts_op(&format!("{name}{c}")))
+                }) {
+                    name.push(c);
+                }
+                if let Some(op) = self.syntax.op(&name) {
+                    return self.parse_call(op);
+                }
+                let more =
+                    self.peek().is_some_and(|c| c.is_ascii_lowercase());
+                match name.chars().next().and_then(Reg::new) {
+                    Some(reg) if name.len() == 1 && !more => {
+                        Ok(Expr::Reg(reg))
+                    }
+                    // Pointing at the first letter, which is ASCII:
+                    Some(_) => Err(Failure {
+                        span: start..start + 1,
+                        reason: Rejection::Expected {
+                            what: "an operand",
+                            found: name.chars().next(),
+                        },
+                    }),
+                    None => Err(self.expected("an operand")),
+                }
+            }
+        }
+    }
+
+    fn parse_reg(&mut self) -> Result<Reg, Failure> {
+        match self.peek().and_then(Reg::new) {
+            Some(reg) => {
+                self.next_if(|_| true);
+                Ok(reg)
+            }
+            None => Err(self.expected("a register")),
+        }
+    }
+
+    fn parse_num(&mut self) -> Result<i64, Failure> {
+        let start = self.offset();
+        let mut num = 0;
+        let mut digits = 0;
+        while let Some(c) = self.next_if(|c| c.is_ascii_digit()) {
+            if digits < MAX_DIGITS {
+                num = num * 10 + i64::from(c as u8 - b'0');
+            }
+            digits += 1;
+        }
+        if digits > MAX_DIGITS {
+            // Too many digits is just more noise, same as no digits:
+            return Err(Failure {
+                span: start..start + digits,
+                reason: Rejection::TooManyDigits(digits),
+            });
+        }
+        Ok(num)
+    }
+
+    fn consume_unit(&mut self) -> Result<(), Failure> {
+        self.consume_char('(')?;
+        self.consume_char(')')
+    }
+
+    fn consume_char(&mut self, c: char) -> Result<(), Failure> {
+        match self.next_if(|found| found == c) {
+            Some(_) => Ok(()),
+            None => Err(self.expected(match c {
+                '(' => "'('",
+                ')' => "')'",
+                ',' => "','",
+                _ => unreachable!(),
+            })),
+        }
+    }
+
+    /// Failure at the next character, which is not `what` was expected.
+    fn expected(&mut self, what: &'static str) -> Failure {
+        let (span, found) = match self.peek_spanned() {
+            Some((span, c)) => (span, Some(c)),
+            None => (self.end..self.end, None),
+        };
+        Failure {
+            span,
+            reason: Rejection::Expected { what, found },
+        }
+    }
 }
 
-fn consume_comma(chars: &mut Peekable<Chars>) -> Option<()> {
-    consume_char(chars, ',')
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Range { start, end } = self.span;
+        write!(f, "{start}..{end}: {}", self.reason)
+    }
 }
 
-fn consume_char(chars: &mut Peekable<Chars>, c: char) -> Option<()> {
-    if c == *chars.peek()? {
-        chars.next().unwrap_or_else(|| unreachable!());
-        Some(())
-    } else {
-        None
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expected {
+                what,
+                found: Some(c),
+            } => write!(f, "expected {what}, found {c:?}"),
+            Self::Expected { what, found: None } => {
+                write!(f, "expected {what}, found end of input")
+            }
+            Self::TooManyDigits(n) => write!(f, "number of {n} digits"),
+            Self::TooDeep => {
+                write!(f, "calls nested more than {MAX_DEPTH} deep")
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
+    use crate::dies_03::{Config, Data};
 
     #[test]
     fn part_1() {
         let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
         let expected =
-            vec![Ix::Mul(2, 4), Ix::Mul(5, 5), Ix::Mul(11, 8), Ix::Mul(8, 5)];
+            vec![Ix::mul(2, 4), Ix::mul(5, 5), Ix::mul(11, 8), Ix::mul(8, 5)];
         assert_eq!(expected, parse_with_regex(input).unwrap());
-        assert_eq!(expected, parse(input));
+        assert_eq!(expected, parse(input, Syntax::Classic));
     }
 
     #[test]
     fn part_2() {
         let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
         let expected = vec![
-            Ix::Mul(2, 4),
+            Ix::mul(2, 4),
             Ix::Dont,
-            Ix::Mul(5, 5),
-            Ix::Mul(11, 8),
+            Ix::mul(5, 5),
+            Ix::mul(11, 8),
             Ix::Do,
-            Ix::Mul(8, 5),
+            Ix::mul(8, 5),
         ];
         assert_eq!(expected, parse_with_regex(input).unwrap());
-        assert_eq!(expected, parse(input));
+        assert_eq!(expected, parse(input, Syntax::Classic));
+    }
+
+    #[test]
+    fn spans() {
+        let input = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+        let (ixs, diagnostics) = diagnose(input, Syntax::Classic);
+        let found: Vec<&str> =
+            ixs.iter().map(|ix| &input[ix.span.clone()]).collect();
+        let expected = vec!["mul(2,4)", "mul(5,5)", "mul(11,8)", "mul(8,5)"];
+        assert_eq!(expected, found);
+        assert_eq!(ixs, parse_spanned(input, Syntax::Classic));
+        let found: Vec<(&str, String)> = diagnostics
+            .iter()
+            .map(|d| (&input[d.span.clone()], d.reason.to_string()))
+            .collect();
+        let expected = [
+            ("mul[", "expected '(', found '['"),
+            ("do_", "expected '(', found '_'"),
+            ("mul(32,64]", "expected ')', found ']'"),
+        ];
+        let expected: Vec<(&str, String)> =
+            expected.map(|(t, r)| (t, r.to_string())).to_vec();
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn extended_diagnostics() {
+        let input = "set(1,2)mul(foo(1,2),3)add(99999999999999999999,1)div(";
+        let (ixs, diagnostics) = diagnose(input, Syntax::Extended);
+        assert!(ixs.is_empty());
+        let found: Vec<String> =
+            diagnostics.iter().map(|d| d.to_string()).collect();
+        let expected = [
+            "0..5: expected a register, found '1'",
+            "8..13: expected an operand, found 'f'",
+            "23..47: number of 20 digits",
+            "50..54: expected an operand, found end of input",
+        ];
+        assert_eq!(&expected[..], found);
+    }
+
+    /// Bits of instructions, whole ones, and noise, to glue together.
+    fn fragment() -> impl Strategy<Value = String> {
+        let digits = "[0-9]{0,5}";
+        prop_oneof![
+            3 => prop::sample::select(vec![
+                "mul", "do", "don't", "()", "(", ")", ",", "'", "n't", "mu",
+                "ul", "d", "o", "m", "u", "l", "t", "n", " ", "\u{e9}",
+                "\u{661}",
+            ])
+            .prop_map(String::from),
+            2 => (digits, digits).prop_map(|(l, r)| format!("mul({l},{r})")),
+            1 => "[0-9]{1,5}",
+            1 => any::<char>().prop_map(String::from),
+        ]
+    }
+
+    #[test]
+    fn keys() {
+        let cases = [
+            ("mudo()", vec![Ix::Do]),
+            ("dodon't()", vec![Ix::Dont]),
+            ("don'do()don()", vec![Ix::Do]),
+            (
+                "mmul(1,2)mul(1,2mul(3,4)",
+                vec![Ix::mul(1, 2), Ix::mul(3, 4)],
+            ),
+            ("muldo(mul(1,2)", vec![Ix::mul(1, 2)]),
+            ("mul(1234,5)mul(0001,2)mul(012,999)", vec![Ix::mul(12, 999)]),
+            ("mul(é,1)mul(1,1)", vec![Ix::mul(1, 1)]),
+            ("add(1,2)", vec![]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expected, parse_with_regex(input).unwrap(), "{input}");
+            assert_eq!(expected, parse(input, Syntax::Classic), "{input}");
+        }
+        let (_, diagnostics) = diagnose("mul(1,1000)", Syntax::Classic);
+        let found: Vec<String> =
+            diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(vec!["0..10: number of 4 digits"], found);
+    }
+
+    #[test]
+    fn extended_keeps_classic() {
+        let cases = [
+            ("add(don't()mul(2,3)", vec![Ix::Dont, Ix::mul(2, 3)]),
+            ("mul(xdo()mul(1,1)", vec![Ix::Do, Ix::mul(1, 1)]),
+            ("mul(undo()mul(3,4)", vec![Ix::Do, Ix::mul(3, 4)]),
+            ("mul(mudo()", vec![Ix::Do]),
+            ("mul(mul(1,2)", vec![Ix::mul(1, 2)]),
+            ("set(x,mul(1,2)do()", vec![Ix::mul(1, 2), Ix::Do]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(expected, parse(input, Syntax::Classic), "{input}");
+            assert_eq!(expected, parse(input, Syntax::Extended), "{input}");
+        }
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = |depth| {
+            (0..depth).fold("1".to_string(), |x, _| format!("add({x},1)"))
+        };
+        let ixs = parse(&nested(MAX_DEPTH), Syntax::Extended);
+        let machine = Data::from(ixs).run(Config::PART_1).unwrap();
+        assert_eq!(1 + MAX_DEPTH as i64, machine.acc);
+        let input = nested(MAX_DEPTH + 1);
+        let (ixs, diagnostics) = diagnose(&input, Syntax::Extended);
+        assert!(ixs.is_empty());
+        let end = 4 * (MAX_DEPTH + 1) - 1;
+        assert_eq!(
+            vec![format!("0..{end}: calls nested more than 64 deep")],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+        );
+        // Deep enough to overflow the stack, were there no limit:
+        let (ixs, diagnostics) =
+            diagnose(&"add(".repeat(1_000_000), Syntax::Extended);
+        assert!(ixs.is_empty());
+        assert_eq!(Rejection::TooDeep, diagnostics[0].reason);
+    }
+
+    /// Like [`fragment`], with bits of the extended syntax too.
+    fn extended_fragment() -> impl Strategy<Value = String> {
+        prop_oneof![
+            4 => fragment(),
+            1 => prop::sample::select(vec![
+                "add", "sub", "div", "set", "a", "s", "x", "set(x,", "mul(x,",
+                "mul(add(1,2),",
+            ])
+            .prop_map(String::from),
+        ]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn extended_finds_classic(
+            parts in prop::collection::vec(extended_fragment(), 0..40),
+        ) {
+            let input = parts.concat();
+            let classic = parse_spanned(&input, Syntax::Classic);
+            let extended = parse_spanned(&input, Syntax::Extended);
+            let toggles = |ixs: &[Spanned]| -> Vec<Spanned> {
+                ixs.iter()
+                    .filter(|s| matches!(s.ix, Ix::Do | Ix::Dont))
+                    .cloned()
+                    .collect()
+            };
+            prop_assert_eq!(toggles(&classic), toggles(&extended));
+            // Every `mul` is found as it is, or nested in a bigger call:
+            for found in &classic {
+                prop_assert!(
+                    extended.iter().any(|s| s == found
+                        || s.span.start <= found.span.start
+                            && found.span.end <= s.span.end
+                            && s.span != found.span),
+                    "{:?} in {:?}",
+                    found,
+                    input,
+                );
+            }
+        }
+
+        #[test]
+        fn agrees_with_regex(
+            parts in prop::collection::vec(fragment(), 0..40),
+        ) {
+            let input = parts.concat();
+            let expected = parse_with_regex(&input).unwrap();
+            prop_assert_eq!(&expected, &parse(&input, Syntax::Classic));
+            let reader = io::BufReader::with_capacity(3, input.as_bytes());
+            let streamed: Vec<Ix> =
+                crate::dies_03::stream(reader, Syntax::Classic)
+                    .map(|s| s.unwrap().ix)
+                    .collect();
+            prop_assert_eq!(&expected, &streamed);
+        }
+
+        #[test]
+        fn never_panics(input in any::<String>()) {
+            for syntax in [Syntax::Classic, Syntax::Extended] {
+                let (ixs, diagnostics) = diagnose(&input, syntax);
+                let spans = ixs
+                    .iter()
+                    .map(|s| &s.span)
+                    .chain(diagnostics.iter().map(|d| &d.span));
+                for span in spans {
+                    prop_assert!(input.get(span.clone()).is_some());
+                }
+            }
+        }
     }
 
     fn parse_with_regex(input: &str) -> anyhow::Result<Vec<Ix>> {
         let re = r#"(?x)
         (?P<do>do\(\))              | # Match "do()"
         (?P<dont>don't\(\))         | # Match "don't()"
-        (?P<mul>mul\((\d+),(\d+)\))   # Match "mul(d1,d2)"
+        (?P<mul>mul\(([0-9]{1,3}),([0-9]{1,3})\)) # Match "mul(d1,d2)"
         "#;
         let re = regex::Regex::new(re)?;
         let mut ixs: Vec<Ix> = Vec::new();
@@ -150,10 +699,10 @@ mod tests {
             } else if cap.name("dont").is_some() {
                 Ix::Dont
             } else if cap.name("mul").is_some() {
-                let left: i32 = cap.get(4).unwrap().as_str().parse().unwrap();
-                let right: i32 =
+                let left: i64 = cap.get(4).unwrap().as_str().parse().unwrap();
+                let right: i64 =
                     cap.get(5).unwrap().as_str().parse().unwrap();
-                Ix::Mul(left, right)
+                Ix::mul(left, right)
             } else {
                 unreachable!();
             };
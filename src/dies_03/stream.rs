@@ -0,0 +1,163 @@
+use std::{
+    io::{self, BufRead},
+    ops::Range,
+};
+
+use super::{
+    parser::{Parser, Spanned},
+    Syntax,
+};
+
+/// Instructions parsed from a reader as it is read, in constant memory,
+/// see [`stream`].
+pub struct Stream<R> {
+    parser: Parser<Chars<R>>,
+    failed: bool,
+}
+
+/// Instructions read incrementally from `reader`. Near misses are skipped,
+/// and the first read error ends the stream.
+pub fn stream<R: BufRead>(reader: R, syntax: Syntax) -> Stream<R> {
+    let chars = Chars { reader, offset: 0 };
+    Stream {
+        parser: Parser::new(chars, syntax),
+        failed: false,
+    }
+}
+
+impl<R: BufRead> Iterator for Stream<R> {
+    type Item = io::Result<Spanned>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        while let Some(found) = self.parser.next_found() {
+            if let Ok(ix) = found {
+                return Some(Ok(ix));
+            }
+        }
+        let e = self.parser.take_error()?;
+        self.failed = true;
+        Some(Err(e))
+    }
+}
+
+/// Characters decoded from UTF-8 one at a time, with their byte ranges, so
+/// a character split across buffer refills comes out whole. Invalid UTF-8
+/// comes out as replacement characters.
+struct Chars<R> {
+    reader: R,
+    offset: usize,
+}
+
+impl<R: BufRead> Chars<R> {
+    fn peek_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => return Ok(buf.first().copied()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn take_byte(&mut self) {
+        self.reader.consume(1);
+        self.offset += 1;
+    }
+}
+
+impl<R: BufRead> Iterator for Chars<R> {
+    type Item = io::Result<(Range<usize>, char)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.offset;
+        let first = match self.peek_byte() {
+            Ok(byte) => byte?,
+            Err(e) => return Some(Err(e)),
+        };
+        self.take_byte();
+        let width = match first {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+        let mut bytes = [first, 0, 0, 0];
+        let mut len = 1;
+        while len < width {
+            match self.peek_byte() {
+                Ok(Some(byte)) if byte & 0xc0 == 0x80 => {
+                    bytes[len] = byte;
+                    len += 1;
+                    self.take_byte();
+                }
+                Ok(_) => break,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let c = match std::str::from_utf8(&bytes[..len]) {
+            Ok(s) if len == width => s.chars().next()?,
+            _ => char::REPLACEMENT_CHARACTER,
+        };
+        Some(Ok((start..self.offset, c)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use super::*;
+    use crate::dies_03::parse_spanned;
+
+    fn collect(input: &[u8], capacity: usize) -> Vec<Spanned> {
+        let reader = BufReader::with_capacity(capacity, input);
+        stream(reader, Syntax::Extended)
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn buffer_boundaries() {
+        let input =
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)\
+            undo()?mul(8,5))é€𝄞set(x,add(1,sub(20,3)))mul(x,x)";
+        let expected = parse_spanned(input, Syntax::Extended);
+        assert_eq!(8, expected.len());
+        for capacity in 1..=12 {
+            assert_eq!(expected, collect(input.as_bytes(), capacity));
+        }
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let input = b"mul(1,2)\xffmul(3,4)\xe2\x82mul(5,6)\xe2\x82";
+        let spans: Vec<Range<usize>> =
+            collect(input, 4).into_iter().map(|s| s.span).collect();
+        assert_eq!(vec![0..8, 9..17, 19..27], spans);
+    }
+
+    /// Reads a few bytes, then fails.
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("disk on fire"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn read_error() {
+        let reader = BufReader::with_capacity(3, Failing(b"mul(1,2)mul(3"));
+        let mut ixs = stream(reader, Syntax::Classic);
+        assert_eq!(0..8, ixs.next().unwrap().unwrap().span);
+        assert!(ixs.next().unwrap().is_err());
+        assert!(ixs.next().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use anyhow::{anyhow, bail};
+
+use super::{Expr, Ix, Op};
+
+/// How a machine treats the program it runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Whether `don't()` disables every instruction until the next `do()`.
+    pub honor_enable: bool,
+}
+
+impl Config {
+    pub const PART_1: Self = Self {
+        honor_enable: false,
+    };
+
+    pub const PART_2: Self = Self { honor_enable: true };
+}
+
+/// State of a running program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub config: Config,
+    pub enabled: bool,
+    /// Sum of every evaluated call's result.
+    pub acc: i64,
+    pub registers: [i64; 26],
+}
+
+impl Machine {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            enabled: true,
+            acc: 0,
+            registers: [0; 26],
+        }
+    }
+
+    pub fn run<'a>(
+        &mut self,
+        ixs: impl IntoIterator<Item = &'a Ix>,
+    ) -> anyhow::Result<()> {
+        for ix in ixs {
+            self.step(ix)?;
+        }
+        Ok(())
+    }
+
+    /// Executes one instruction. Fails on overflow or division by zero.
+    pub fn step(&mut self, ix: &Ix) -> anyhow::Result<()> {
+        match ix {
+            Ix::Do => self.enabled = true,
+            Ix::Dont => self.enabled = false,
+            _ if self.config.honor_enable && !self.enabled => (),
+            Ix::Eval(expr) => {
+                let value = self.eval(expr)?;
+                self.acc = self
+                    .acc
+                    .checked_add(value)
+                    .ok_or_else(|| anyhow!("Accumulator overflows."))?;
+            }
+            Ix::Set(reg, expr) => {
+                self.registers[reg.index()] = self.eval(expr)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Recurses into nested calls, which the parser never nests more
+    /// than [`MAX_DEPTH`](super::MAX_DEPTH) deep.
+    fn eval(&self, expr: &Expr) -> anyhow::Result<i64> {
+        let value = match expr {
+            Expr::Num(n) => *n,
+            Expr::Reg(reg) => self.registers[reg.index()],
+            Expr::Call(op, left, right) => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                let value = match op {
+                    Op::Add => left.checked_add(right),
+                    Op::Sub => left.checked_sub(right),
+                    Op::Mul => left.checked_mul(right),
+                    Op::Div if right == 0 => bail!("Division by zero."),
+                    Op::Div => left.checked_div(right),
+                };
+                value.ok_or_else(|| {
+                    anyhow!("{op:?}({left},{right}) overflows.")
+                })?
+            }
+        };
+        Ok(value)
+    }
+}
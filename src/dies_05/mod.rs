@@ -0,0 +1,310 @@
+mod report;
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+pub use report::{Correction, Move, Report, Violation};
+
+use crate::{
+    error::{self, ParseError, Reason},
+    graph::Graph,
+    Solution,
+};
+
+type Node = i32;
+type Rule = (Node, Node);
+type Rules = Vec<Rule>;
+type Update = Vec<Node>;
+type Updates = Vec<Update>;
+
+pub struct Data {
+    rules: Rules,
+    updates: Updates,
+    graph: Graph<Node>,
+    cycles: Cycles,
+    ambiguity: Ambiguity,
+}
+
+/// What to do when the rules between an update's pages contradict each
+/// other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Cycles {
+    /// Fail, naming the update and a cycle of its rules.
+    #[default]
+    Fail,
+    /// Ignore a smallest set of the update's rules which breaks every
+    /// cycle, see [`Graph::feedback_arc_set`].
+    DropRules,
+}
+
+/// What to do when the rules between an update's pages allow more than
+/// one order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ambiguity {
+    /// Keep pages which no rule orders in the order of the update.
+    #[default]
+    Stable,
+    /// Fail, naming the update and the pairs of pages no rule orders.
+    Fail,
+}
+
+/// Update put in order, along with the rules ignored to do so, and the
+/// pairs of pages which no remaining rule orders, earlier page first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reordered {
+    pub pages: Vec<Node>,
+    pub dropped: Vec<Rule>,
+    pub unordered: Vec<(Node, Node)>,
+}
+
+impl Data {
+    pub fn with_cycles(self, cycles: Cycles) -> Self {
+        Self { cycles, ..self }
+    }
+
+    pub fn with_ambiguity(self, ambiguity: Ambiguity) -> Self {
+        Self { ambiguity, ..self }
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules[..]
+    }
+
+    pub fn updates(&self) -> &[Update] {
+        &self.updates[..]
+    }
+
+    /// Whether each page comes right before one which a rule says must
+    /// follow it. Stricter than breaking no rule, see [`Data::validate`].
+    pub fn is_ordered(&self, update: &[Node]) -> bool {
+        update
+            .windows(2)
+            .all(|pair| self.graph.has_edge(&pair[0], &pair[1]))
+    }
+
+    /// The pages of `update` in an order which follows the rules between
+    /// them, unless they contradict each other, see [`Cycles`], or allow
+    /// more than one order, see [`Ambiguity`]. Ties are always broken the
+    /// same way, by position in the update. Fails if a page comes twice,
+    /// as there is then no telling which of its places a rule is about.
+    pub fn reorder(&self, update: &[Node]) -> anyhow::Result<Reordered> {
+        let mut position: HashMap<Node, usize> = HashMap::new();
+        for (i, &page) in update.iter().enumerate() {
+            if position.insert(page, i).is_some() {
+                bail!("Update {update:?} has page {page} more than once.");
+            }
+        }
+        let mut g = self.graph.induced(update);
+        // Including pages no rule mentions:
+        for page in update {
+            g.add_node(*page);
+        }
+        let key = |page: &Node| position[page];
+        let (g, pages, dropped) = match (g.topo_sort_by_key(key), self.cycles)
+        {
+            (Ok(pages), _) => (g, pages, Vec::new()),
+            (Err(cycle), Cycles::Fail) => {
+                bail!("Update {update:?} has contradictory rules: {cycle}")
+            }
+            (Err(_), Cycles::DropRules) => {
+                let dropped = g.feedback_arc_set_by_key(key)?;
+                let g = g.without_edges(&dropped);
+                let pages = g.topo_sort_by_key(key)?;
+                (g, pages, dropped)
+            }
+        };
+        let mut unordered = Vec::new();
+        if !g.is_only_order(&pages) {
+            unordered = g
+                .unordered_pairs()
+                .into_iter()
+                .map(|(a, b)| if key(&a) < key(&b) { (a, b) } else { (b, a) })
+                .collect();
+            unordered.sort_by_key(|(a, b)| (key(a), key(b)));
+        }
+        if !unordered.is_empty() && self.ambiguity == Ambiguity::Fail {
+            let pairs: Vec<String> =
+                unordered.iter().map(|(a, b)| format!("{a}|{b}")).collect();
+            bail!(
+                "Update {update:?} has more than one order, as no rule \
+                orders {}.",
+                pairs.join(", ")
+            );
+        }
+        Ok(Reordered {
+            pages,
+            dropped,
+            unordered,
+        })
+    }
+}
+
+impl Solution for Data {
+    const DAY: u8 = 5;
+
+    type Answer1 = Node;
+    type Answer2 = i32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l));
+        let mut rules: Rules = Vec::new();
+        let mut updates: Updates = Vec::new();
+        for (line_num, line) in
+            lines.by_ref().take_while(|(_, line)| !line.is_empty())
+        {
+            let parse =
+                |field| error::parse_num(Self::DAY, line_num, line, field);
+            let rule: Rule = match line.split_once('|') {
+                Some((a, b)) => (parse(a)?, parse(b)?),
+                None => {
+                    let reason = Reason::MissingSeparator { separator: "|" };
+                    let column = line.len() + 1;
+                    return Err(ParseError::new(
+                        Self::DAY,
+                        line_num,
+                        column,
+                        reason,
+                    ));
+                }
+            };
+            rules.push(rule);
+        }
+        for (line_num, line) in lines {
+            let mut update: Update = Vec::new();
+            for field in line.split(',') {
+                let page =
+                    error::parse_num(Self::DAY, line_num, line, field)?;
+                if update.contains(&page) {
+                    return Err(ParseError::at(
+                        Self::DAY,
+                        line_num,
+                        line,
+                        field,
+                        Reason::Duplicate("page"),
+                    ));
+                }
+                update.push(page);
+            }
+            updates.push(update);
+        }
+        let graph = Graph::from_edges(rules.iter().copied());
+        Ok(Self {
+            rules,
+            updates,
+            graph,
+            cycles: Cycles::default(),
+            ambiguity: Ambiguity::default(),
+        })
+    }
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
+        self.updates
+            .iter()
+            .filter(|update| self.is_ordered(update))
+            .map(|update| middle(update))
+            .sum()
+    }
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
+        self.updates
+            .iter()
+            .filter(|update| !self.is_ordered(update))
+            .map(|update| middle(&self.reorder(update)?.pages))
+            .sum()
+    }
+}
+
+fn middle(update: &[Node]) -> anyhow::Result<Node> {
+    let n = update.len();
+    if n % 2 != 1 {
+        bail!("Even number of pages in update: {update:?}");
+    }
+    Ok(update[n / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CYCLIC: &str = "47|53\n53|29\n29|47\n47|13\n53|13\n13|61\n\n\
+        13,29,53,47,61\n47,13,61\n";
+
+    #[test]
+    fn cycle_reported() {
+        let data = Data::parse(CYCLIC).unwrap();
+        assert_eq!(Ok(13), data.part1().map_err(|e| e.to_string()));
+        let e = data.part2().unwrap_err().to_string();
+        let cycles = [
+            "47 -> 53 -> 29 -> 47",
+            "53 -> 29 -> 47 -> 53",
+            "29 -> 47 -> 53 -> 29",
+        ];
+        assert!(
+            e.starts_with(
+                "Update [13, 29, 53, 47, 61] has contradictory rules: "
+            ),
+            "{e}"
+        );
+        assert!(cycles.iter().any(|c| e.ends_with(c)), "{e}");
+    }
+
+    #[test]
+    fn unruled_pages() {
+        let data = Data::parse("1|2\n\n2,7,1").unwrap();
+        let reordered = data.reorder(&data.updates()[0]).unwrap();
+        assert_eq!(vec![7, 1, 2], reordered.pages);
+        assert_eq!(vec![(2, 7), (7, 1)], reordered.unordered);
+        assert_eq!(1, data.part2().unwrap());
+        let data = data.with_ambiguity(Ambiguity::Fail);
+        assert!(data.part2().is_err());
+    }
+
+    #[test]
+    fn adjacent_rules() {
+        // No rule is broken, but none puts 7 right before or after a page:
+        let data = Data::parse("1|2\n\n1,7,2").unwrap();
+        assert!(data.validate(&data.updates()[0]).violations.is_empty());
+        assert!(!data.is_ordered(&data.updates()[0]));
+        assert_eq!(0, data.part1().unwrap());
+        assert_eq!(7, data.part2().unwrap());
+    }
+
+    #[test]
+    fn repeated_pages() {
+        let e = Data::parse("1|2\n\n2,7,1\n1,2,7,1").err().unwrap();
+        assert_eq!((4, 7), (e.line, e.column));
+        assert_eq!(Reason::Duplicate("page"), e.reason);
+        let data = Data::parse("1|2\n\n2,1").unwrap();
+        let e = data.reorder(&[2, 7, 1, 7]).unwrap_err();
+        assert_eq!(
+            "Update [2, 7, 1, 7] has page 7 more than once.",
+            e.to_string()
+        );
+    }
+
+    #[test]
+    fn rules_dropped() {
+        let data = Data::parse(CYCLIC)
+            .unwrap()
+            .with_cycles(Cycles::DropRules)
+            .with_ambiguity(Ambiguity::Stable);
+        let update = &data.updates()[0];
+        let Reordered {
+            pages,
+            dropped,
+            unordered,
+        } = data.reorder(update).unwrap();
+        assert_eq!(vec![47, 53, 13, 29, 61], pages);
+        assert_eq!(vec![(29, 47)], dropped);
+        assert_eq!(vec![(13, 29), (29, 61)], unordered);
+        assert_eq!(13, data.part2().unwrap());
+        // Dropping the rule leaves 29 free to go anywhere after 53:
+        let data = data.with_ambiguity(Ambiguity::Fail);
+        let e = data.part2().unwrap_err().to_string();
+        let expected =
+            "Update [13, 29, 53, 47, 61] has more than one order, \
+            as no rule orders 13|29, 29|61.";
+        assert_eq!(expected, e);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{cmp::Reverse, fmt};
+
+use super::{Data, Node, Rule};
+
+/// Why an update is out of order, and how to put it in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub update: Vec<Node>,
+    /// Every rule whose pages the update has the wrong way round.
+    pub violations: Vec<Violation>,
+    /// How to put the update in order, or why there is no telling, see
+    /// [`Data::reorder`].
+    pub correction: Result<Correction, String>,
+}
+
+/// Order following the rules, and how to get there from the update.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Correction {
+    /// Rules ignored to find the order, see [`Cycles`](super::Cycles).
+    pub dropped: Vec<Rule>,
+    pub pages: Vec<Node>,
+    /// Fewest moves of single pages turning the update into the order, to
+    /// be made one after the other.
+    pub moves: Vec<Move>,
+}
+
+/// Rule broken by an update, with the 0-based positions of its pages,
+/// `after` coming first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub rule: Rule,
+    pub before_at: usize,
+    pub after_at: usize,
+}
+
+/// Takes `page` out of the update at 0-based position `from`, then puts
+/// it back in at `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: Node,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Data {
+    /// Every rule `update` breaks, and the moves which fix it, if any do.
+    pub fn validate(&self, update: &[Node]) -> Report {
+        let mut violations = Vec::new();
+        for (after_at, after) in update.iter().enumerate() {
+            for (before_at, before) in
+                update.iter().enumerate().skip(after_at + 1)
+            {
+                if self.graph.has_edge(before, after) {
+                    violations.push(Violation {
+                        rule: (*before, *after),
+                        before_at,
+                        after_at,
+                    });
+                }
+            }
+        }
+        let correction = if violations.is_empty() {
+            Ok(Correction {
+                dropped: Vec::new(),
+                pages: update.to_vec(),
+                moves: Vec::new(),
+            })
+        } else {
+            self.reorder(update)
+                .map(|reordered| Correction {
+                    moves: moves(update, &reordered.pages),
+                    dropped: reordered.dropped,
+                    pages: reordered.pages,
+                })
+                .map_err(|e| e.to_string())
+        };
+        Report {
+            update: update.to_vec(),
+            violations,
+            correction,
+        }
+    }
+}
+
+impl Report {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Fewest moves turning `from` into `to`, its pages in another order: the
+/// pages of a longest run which is in the same order in both stay put.
+/// Neither may have a page twice, see [`Data::reorder`].
+fn moves(from: &[Node], to: &[Node]) -> Vec<Move> {
+    let position = |pages: &[Node], page: Node| {
+        pages
+            .iter()
+            .position(|&p| p == page)
+            .unwrap_or_else(|| unreachable!("{page} missing from {pages:?}"))
+    };
+    let at: Vec<usize> =
+        to.iter().map(|&page| position(from, page)).collect();
+    // Longest increasing subsequence of `at`, as the length of the longest
+    // one ending at each page, and the page before it there:
+    let mut len = vec![1; at.len()];
+    let mut prev: Vec<Option<usize>> = vec![None; at.len()];
+    for i in 0..at.len() {
+        for j in 0..i {
+            if at[j] < at[i] && len[j] + 1 > len[i] {
+                len[i] = len[j] + 1;
+                prev[i] = Some(j);
+            }
+        }
+    }
+    let mut stay = vec![false; at.len()];
+    let mut end = (0..at.len()).max_by_key(|&i| (len[i], Reverse(i)));
+    while let Some(i) = end {
+        stay[i] = true;
+        end = prev[i];
+    }
+    // Each page which moves goes right after the one before it in `to`,
+    // which is by then in its place relative to the others:
+    let mut pages = from.to_vec();
+    let mut moves = Vec::new();
+    for (i, &page) in to.iter().enumerate() {
+        if stay[i] {
+            continue;
+        }
+        let from = position(&pages, page);
+        pages.remove(from);
+        let to = match i {
+            0 => 0,
+            _ => position(&pages, to[i - 1]) + 1,
+        };
+        pages.insert(to, page);
+        moves.push(Move { page, from, to });
+    }
+    moves
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |pages: &[Node]| -> String {
+            let pages: Vec<String> =
+                pages.iter().map(|p| p.to_string()).collect();
+            pages.join(",")
+        };
+        if self.is_valid() {
+            return writeln!(f, "{}: in order", list(&self.update));
+        }
+        writeln!(f, "{}: out of order", list(&self.update))?;
+        for v in &self.violations {
+            let (before, after) = v.rule;
+            writeln!(
+                f,
+                "  breaks {before}|{after}: {before} at {}, {after} at {}",
+                v.before_at, v.after_at
+            )?;
+        }
+        let correction = match &self.correction {
+            Ok(correction) => correction,
+            Err(reason) => return writeln!(f, "  not corrected: {reason}"),
+        };
+        for (before, after) in &correction.dropped {
+            writeln!(f, "  ignoring {before}|{after}, to break a cycle")?;
+        }
+        let n = correction.moves.len();
+        let s = if n == 1 { "" } else { "s" };
+        writeln!(
+            f,
+            "  corrected to {} in {n} move{s}:",
+            list(&correction.pages),
+        )?;
+        for Move { page, from, to } in &correction.moves {
+            writeln!(f, "    {page} from {from} to {to}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dies_05::Ambiguity, Solution};
+
+    const RULES: &str = "47|53\n97|13\n97|61\n97|47\n75|29\n61|13\n75|53\n\
+        29|13\n97|29\n53|29\n61|53\n97|53\n61|29\n47|13\n75|47\n97|75\n\
+        47|61\n75|61\n47|29\n75|13\n53|13\n\n";
+
+    #[test]
+    fn report() {
+        let data = Data::parse(&format!("{RULES}75,97,47,61,53")).unwrap();
+        let report = data.validate(&data.updates()[0]);
+        let expected = "75,97,47,61,53: out of order\n  \
+            breaks 97|75: 97 at 1, 75 at 0\n  \
+            corrected to 97,75,47,61,53 in 1 move:\n    \
+            75 from 0 to 1\n";
+        assert_eq!(expected, report.to_string());
+        let report = data.validate(&[75, 47, 61, 53, 29]);
+        assert!(report.is_valid());
+        assert_eq!(Ok(0), report.correction.as_ref().map(|c| c.moves.len()));
+        assert_eq!("75,47,61,53,29: in order\n", report.to_string());
+        let report = data.validate(&[97, 13, 75, 29, 47]);
+        let broken: Vec<(Rule, usize, usize)> = report
+            .violations
+            .iter()
+            .map(|v| (v.rule, v.before_at, v.after_at))
+            .collect();
+        let expected = [
+            ((75, 13), 2, 1),
+            ((29, 13), 3, 1),
+            ((47, 13), 4, 1),
+            ((47, 29), 4, 3),
+        ];
+        assert_eq!(&expected[..], broken);
+        let correction = report.correction.unwrap();
+        assert_eq!(vec![97, 75, 47, 29, 13], correction.pages);
+        assert_eq!(2, correction.moves.len());
+    }
+
+    #[test]
+    fn not_corrected() {
+        let data = Data::parse("1|2\n\n2,7,1")
+            .unwrap()
+            .with_ambiguity(Ambiguity::Fail);
+        let report = data.validate(&data.updates()[0]);
+        let expected = "2,7,1: out of order\n  \
+            breaks 1|2: 1 at 2, 2 at 0\n  \
+            not corrected: Update [2, 7, 1] has more than one order, as no \
+            rule orders 2|7, 7|1.\n";
+        assert_eq!(expected, report.to_string());
+        let report = data.validate(&[2, 7, 1, 7, 2]);
+        assert_eq!(1, report.violations.len());
+        let expected = "Update [2, 7, 1, 7, 2] has page 7 more than once.";
+        assert_eq!(Err(expected.to_string()), report.correction);
+    }
+
+    #[test]
+    fn fewest_moves() {
+        // Every order of five pages, against a known count of moves:
+        // the pages outside a longest increasing run.
+        let to = [1, 2, 3, 4, 5];
+        let mut from = to;
+        let mut count = 0;
+        permute(&mut from, 0, &mut |from| {
+            let moves = moves(from, &to);
+            let mut pages = from.to_vec();
+            for m in &moves {
+                assert_eq!(m.page, pages.remove(m.from));
+                pages.insert(m.to, m.page);
+            }
+            assert_eq!(&to[..], pages, "{from:?}: {moves:?}");
+            assert_eq!(5 - longest_increasing(from), moves.len());
+            count += 1;
+        });
+        assert_eq!(120, count);
+    }
+
+    fn permute(pages: &mut [Node; 5], k: usize, f: &mut impl FnMut(&[Node])) {
+        if k == pages.len() {
+            return f(&pages[..]);
+        }
+        for i in k..pages.len() {
+            pages.swap(k, i);
+            permute(pages, k + 1, f);
+            pages.swap(k, i);
+        }
+    }
+
+    /// Length of a longest increasing subsequence, by brute force.
+    fn longest_increasing(pages: &[Node]) -> usize {
+        (0u32..1 << pages.len())
+            .filter(|set| {
+                let kept: Vec<Node> = (0..pages.len())
+                    .filter(|i| set & 1 << i != 0)
+                    .map(|i| pages[i])
+                    .collect();
+                kept.windows(2).all(|w| w[0] < w[1])
+            })
+            .map(|set| set.count_ones() as usize)
+            .max()
+            .unwrap_or(0)
+    }
+}
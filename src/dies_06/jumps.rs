@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use crate::grid::{Grid, Pos};
+
+use super::Dir;
+
+/// For every cell and direction, the cell where the guard would stop,
+/// right before the next obstacle, or `None` if it would walk off the map.
+/// Lets a patrol jump from turn to turn instead of stepping cell by cell.
+pub struct Jumps {
+    stops: Grid<[Option<Pos>; 4]>,
+}
+
+impl Jumps {
+    pub fn new(grid: &Grid<bool>) -> Self {
+        let mut stops = Grid::filled(grid.rows(), grid.cols(), [None; 4]);
+        let forward: Vec<Pos> = grid.positions().collect();
+        for dir in [Dir::N, Dir::E, Dir::S, Dir::W] {
+            // The cell ahead must be resolved before the one behind it:
+            let positions: Box<dyn Iterator<Item = &Pos>> = match dir {
+                Dir::N | Dir::W => Box::new(forward.iter()),
+                Dir::S | Dir::E => Box::new(forward.iter().rev()),
+            };
+            for pos in positions {
+                let stop = match grid.step(*pos, dir.offset()) {
+                    None => None,
+                    Some(ahead) if grid[ahead] => Some(*pos),
+                    Some(ahead) => stops[ahead][dir.index()],
+                };
+                stops[*pos][dir.index()] = stop;
+            }
+        }
+        Self { stops }
+    }
+
+    /// Where the guard stops when walking from `pos` towards `dir`, given
+    /// one extra obstruction, overlaid on the original map.
+    fn next(&self, pos: Pos, dir: Dir, obstruction: Pos) -> Option<Pos> {
+        let stop = self.stops[pos][dir.index()];
+        let o = obstruction;
+        let is_ahead = match dir {
+            Dir::N => o.k == pos.k && o.r < pos.r,
+            Dir::S => o.k == pos.k && o.r > pos.r,
+            Dir::W => o.r == pos.r && o.k < pos.k,
+            Dir::E => o.r == pos.r && o.k > pos.k,
+        };
+        let is_before_stop = match (dir, stop) {
+            (_, None) => true,
+            (Dir::N, Some(s)) => o.r >= s.r,
+            (Dir::S, Some(s)) => o.r <= s.r,
+            (Dir::W, Some(s)) => o.k >= s.k,
+            (Dir::E, Some(s)) => o.k <= s.k,
+        };
+        if is_ahead && is_before_stop {
+            self.stops.step(o, dir.offset().rev())
+        } else {
+            stop
+        }
+    }
+
+    /// Would the guard, starting at `pos` facing `dir`, patrol forever if
+    /// the `obstruction` was added?
+    pub fn loops(
+        &self,
+        mut pos: Pos,
+        mut dir: Dir,
+        obstruction: Pos,
+    ) -> bool {
+        let mut turns: HashSet<(Pos, Dir)> = HashSet::new();
+        while let Some(stop) = self.next(pos, dir, obstruction) {
+            pos = stop;
+            dir = dir.turn();
+            if !turns.insert((pos, dir)) {
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,380 @@
+mod jumps;
+mod render;
+
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use anyhow::anyhow;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+pub use render::{write_frames, Trace};
+
+use crate::{
+    error::{ParseError, Reason},
+    grid::{Grid, Offset, Pos},
+    Solution,
+};
+
+pub struct Data {
+    guard: Guard,
+    grid: Grid<bool>,
+}
+
+impl Solution for Data {
+    const DAY: u8 = 6;
+
+    type Answer1 = usize;
+    type Answer2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut guards: Vec<Guard> = Vec::new();
+        let grid = Grid::parse(Self::DAY, input, |pos, c| {
+            guards.extend(Guard::from_char(c, pos));
+            c == '#'
+        })?;
+        let guard = match &guards[..] {
+            [guard] => guard.clone(),
+            [] => {
+                let reason = Reason::Missing("guard");
+                return Err(ParseError::new(
+                    Self::DAY,
+                    grid.rows() + 1,
+                    1,
+                    reason,
+                ));
+            }
+            [_, Guard { pos, .. }, ..] => {
+                let reason = Reason::Duplicate("guard");
+                return Err(ParseError::new(
+                    Self::DAY,
+                    pos.r + 1,
+                    pos.k + 1,
+                    reason,
+                ));
+            }
+        };
+        Ok(Self { grid, guard })
+    }
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
+        Ok(self.path().len())
+    }
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
+        // An obstruction off the original path is never run into, so
+        // cannot change anything:
+        let mut candidates = self.path();
+        candidates.remove(&self.guard.pos);
+        let jumps = jumps::Jumps::new(&self.grid);
+        let count = candidates
+            .par_iter()
+            .filter(|o| jumps.loops(self.guard.pos, self.guard.dir, **o))
+            .count();
+        Ok(count)
+    }
+}
+
+impl Data {
+    /// Unique positions visited by the unobstructed patrol.
+    fn path(&self) -> HashSet<Pos> {
+        let mut guard = self.guard.clone();
+        guard.patrol(&self.grid);
+        guard.visits.keys().map(|(pos, _)| *pos).collect()
+    }
+
+    /// Replays the patrol step by step, see [`Trace`].
+    pub fn trace(&self, obstruction: Option<Pos>) -> anyhow::Result<Trace> {
+        Trace::new(self, obstruction)
+    }
+
+    /// The map at the end of the patrol, see [`Trace::render`].
+    pub fn render(&self, obstruction: Option<Pos>) -> anyhow::Result<String> {
+        let mut trace = self.trace(obstruction)?;
+        trace.finish();
+        Ok(trace.render())
+    }
+
+    /// Runs the guard's patrol to its end, optionally with one extra
+    /// obstruction placed on the map. Fails if the obstruction is off the
+    /// map.
+    pub fn patrol(
+        &self,
+        obstruction: Option<Pos>,
+    ) -> anyhow::Result<Outcome> {
+        let mut guard = self.guard.clone();
+        let outcome = match obstruction {
+            None => guard.patrol(&self.grid),
+            Some(pos) => guard.patrol(&self.obstructed(pos)?),
+        };
+        Ok(outcome)
+    }
+
+    /// The map with an extra obstruction at `pos`, if that is on it.
+    fn obstructed(&self, pos: Pos) -> anyhow::Result<Grid<bool>> {
+        let mut grid = self.grid.clone();
+        let cell = grid.get_mut(pos).ok_or_else(|| {
+            anyhow!(
+                "Obstruction at row {}, column {} is off the {}x{} map.",
+                pos.r,
+                pos.k,
+                self.grid.rows(),
+                self.grid.cols()
+            )
+        })?;
+        *cell = true;
+        Ok(grid)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    N,
+    E,
+    S,
+    W,
+}
+
+impl Dir {
+    fn turn(&self) -> Self {
+        match self {
+            Self::N => Self::E,
+            Self::E => Self::S,
+            Self::S => Self::W,
+            Self::W => Self::N,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::N => 0,
+            Self::E => 1,
+            Self::S => 2,
+            Self::W => 3,
+        }
+    }
+
+    fn offset(&self) -> Offset {
+        match self {
+            Self::N => Offset::N,
+            Self::E => Offset::E,
+            Self::S => Offset::S,
+            Self::W => Offset::W,
+        }
+    }
+}
+
+enum View {
+    Exit,
+    Obstructed,
+    Free(Pos),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Exited,
+    /// The guard re-entered a state (position and direction) it was
+    /// already in, so it will repeat the same `len` steps from there
+    /// forever. A step is either a move or a turn.
+    Looped {
+        start: Pos,
+        dir: Dir,
+        len: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Guard {
+    dir: Dir,
+    pos: Pos,
+    /// Step number at which each state was first entered.
+    visits: HashMap<(Pos, Dir), usize>,
+    steps: usize,
+}
+
+impl Guard {
+    fn turn(&mut self) {
+        self.dir = self.dir.turn();
+    }
+
+    fn move_to(&mut self, pos: Pos) {
+        self.pos = pos;
+    }
+
+    /// Records the current state, unless it was already visited, in which
+    /// case returns the length of the cycle back to it.
+    fn record(&mut self) -> Option<usize> {
+        self.steps += 1;
+        match self.visits.entry((self.pos, self.dir)) {
+            Entry::Occupied(entry) => Some(self.steps - entry.get()),
+            Entry::Vacant(entry) => {
+                entry.insert(self.steps);
+                None
+            }
+        }
+    }
+
+    fn look(&self, grid: &Grid<bool>) -> View {
+        match grid.step(self.pos, self.dir.offset()) {
+            None => View::Exit,
+            Some(pos) if grid[pos] => View::Obstructed,
+            Some(pos) => View::Free(pos),
+        }
+    }
+
+    fn patrol(&mut self, grid: &Grid<bool>) -> Outcome {
+        loop {
+            if let Some(outcome) = self.step(grid) {
+                return outcome;
+            }
+        }
+    }
+
+    /// Turns or moves once, unless that ends the patrol.
+    fn step(&mut self, grid: &Grid<bool>) -> Option<Outcome> {
+        match self.look(grid) {
+            View::Exit => {
+                return Some(Outcome::Exited);
+            }
+            View::Obstructed => {
+                self.turn();
+            }
+            View::Free(pos) => {
+                self.move_to(pos);
+            }
+        }
+        self.record().map(|len| Outcome::Looped {
+            start: self.pos,
+            dir: self.dir,
+            len,
+        })
+    }
+}
+
+impl Guard {
+    fn from_char(c: char, pos: Pos) -> Option<Self> {
+        let dir = match c.to_ascii_uppercase() {
+            '^' => Dir::N,
+            '>' => Dir::E,
+            'V' => Dir::S,
+            '<' => Dir::W,
+            _ => return None,
+        };
+        let visited = HashMap::from([((pos, dir), 0)]);
+        let selph = Self {
+            dir,
+            pos,
+            visits: visited,
+            steps: 0,
+        };
+        Some(selph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn loop_detection() {
+        let data = Data::parse(EXAMPLE).unwrap();
+        assert_eq!(Outcome::Exited, data.patrol(None).unwrap());
+        // Obstruction right next to the guard's starting position:
+        let outcome = data.patrol(Some(Pos { r: 6, k: 3 })).unwrap();
+        let expected = Outcome::Looped {
+            start: Pos { r: 6, k: 4 },
+            dir: Dir::N,
+            // Moves, each followed by a turn: N 5, E 4, S 5, W 4.
+            len: 5 + 1 + 4 + 1 + 5 + 1 + 4 + 1,
+        };
+        assert_eq!(expected, outcome);
+        let e = data.patrol(Some(Pos { r: 10, k: 3 })).unwrap_err();
+        assert_eq!(
+            "Obstruction at row 10, column 3 is off the 10x10 map.",
+            e.to_string()
+        );
+    }
+
+    #[test]
+    fn jumps_agree_with_steps() {
+        let data = Data::parse(EXAMPLE).unwrap();
+        let jumps = jumps::Jumps::new(&data.grid);
+        let Guard { pos, dir, .. } = data.guard;
+        for (o, is_obstructed) in data.grid.iter() {
+            if !is_obstructed && o != pos {
+                let stepped = matches!(
+                    data.patrol(Some(o)).unwrap(),
+                    Outcome::Looped { .. }
+                );
+                assert_eq!(stepped, jumps.loops(pos, dir, o), "{o:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn render() {
+        let data = Data::parse(EXAMPLE).unwrap();
+        let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+";
+        let rendered = data.render(Some(Pos { r: 6, k: 3 })).unwrap();
+        assert_eq!(expected, rendered);
+        let frames: Vec<String> = data.trace(None).unwrap().collect();
+        assert_eq!(EXAMPLE, frames[0].trim_end());
+        let second: Vec<&str> = frames[1].lines().collect();
+        assert_eq!(["....^.....", ".#..|....."], second[5..7]);
+        assert_eq!(&data.render(None).unwrap(), frames.last().unwrap());
+        let e = data.trace(Some(Pos { r: 500, k: 500 })).err().unwrap();
+        assert_eq!(
+            "Obstruction at row 500, column 500 is off the 10x10 map.",
+            e.to_string()
+        );
+    }
+
+    #[test]
+    fn boxed_in() {
+        let data = Data::parse(".#.\n#^#\n.#.").unwrap();
+        let expected = Outcome::Looped {
+            start: Pos { r: 1, k: 1 },
+            dir: Dir::N,
+            len: 4,
+        };
+        assert_eq!(expected, data.patrol(None).unwrap());
+    }
+
+    #[test]
+    fn larger_than_heuristics() {
+        // A loop longer than the former 6,000-move limit.
+        let n = 2_000;
+        let mut rows = vec![vec!['.'; n]; n];
+        rows[0][1] = '#';
+        rows[1][n - 1] = '#';
+        rows[n - 1][n - 2] = '#';
+        rows[n - 2][0] = '#';
+        rows[n - 2][1] = '^';
+        let input: Vec<String> =
+            rows.iter().map(|row| row.iter().collect()).collect();
+        let data = Data::parse(&input.join("\n")).unwrap();
+        let outcome = data.patrol(None).unwrap();
+        assert!(matches!(outcome, Outcome::Looped { .. }));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{fs, io, path::Path};
+
+use crate::grid::{Grid, Pos};
+
+use super::{Data, Dir, Guard, Outcome};
+
+/// Directions in which the guard has walked through a cell.
+#[derive(Debug, Clone, Copy, Default)]
+struct Trail {
+    vertical: bool,
+    horizontal: bool,
+}
+
+impl Trail {
+    fn mark(&mut self, dir: Dir) {
+        match dir {
+            Dir::N | Dir::S => self.vertical = true,
+            Dir::E | Dir::W => self.horizontal = true,
+        }
+    }
+}
+
+/// Step-by-step replay of a patrol, keeping the path walked so far.
+///
+/// As an iterator, yields the rendered map before the first step and
+/// after every step, until the guard exits or starts looping.
+pub struct Trace {
+    grid: Grid<bool>,
+    obstruction: Option<Pos>,
+    guard: Guard,
+    trail: Grid<Trail>,
+    outcome: Option<Outcome>,
+    started: bool,
+}
+
+impl Trace {
+    /// Fails if the obstruction is off the map.
+    pub fn new(
+        data: &Data,
+        obstruction: Option<Pos>,
+    ) -> anyhow::Result<Self> {
+        let grid = match obstruction {
+            None => data.grid.clone(),
+            Some(pos) => data.obstructed(pos)?,
+        };
+        let guard = data.guard.clone();
+        let mut trail = grid.map(|_| Trail::default());
+        trail[guard.pos].mark(guard.dir);
+        Ok(Self {
+            grid,
+            obstruction,
+            guard,
+            trail,
+            outcome: None,
+            started: false,
+        })
+    }
+
+    /// How the patrol ended, once it has.
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn steps(&self) -> usize {
+        self.guard.steps
+    }
+
+    /// Advances by one turn or move. Returns `false` once the patrol is
+    /// over.
+    pub fn step(&mut self) -> bool {
+        if self.outcome.is_some() {
+            return false;
+        }
+        let before = (self.guard.pos, self.guard.dir);
+        self.outcome = self.guard.step(&self.grid);
+        let (pos, dir) = (self.guard.pos, self.guard.dir);
+        if (pos, dir) != before {
+            // Turning marks the cell with both directions, i.e. a corner.
+            self.trail[before.0].mark(dir);
+            self.trail[pos].mark(dir);
+        }
+        self.outcome.is_none()
+    }
+
+    pub fn finish(&mut self) -> Outcome {
+        while self.step() {}
+        self.outcome.unwrap_or_else(|| unreachable!())
+    }
+
+    /// The map with the path walked so far: `|` and `-` for cells walked
+    /// through vertically and horizontally, `+` for both (turns and
+    /// crossings), `O` for the added obstruction and the guard itself as
+    /// one of `^`, `>`, `v`, `<`.
+    pub fn render(&self) -> String {
+        self.grid.render(|pos, is_obstructed| {
+            let trail = self.trail[pos];
+            if pos == self.guard.pos {
+                match self.guard.dir {
+                    Dir::N => '^',
+                    Dir::E => '>',
+                    Dir::S => 'v',
+                    Dir::W => '<',
+                }
+            } else if Some(pos) == self.obstruction {
+                'O'
+            } else if *is_obstructed {
+                '#'
+            } else {
+                match (trail.vertical, trail.horizontal) {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => '.',
+                }
+            }
+        })
+    }
+}
+
+impl Iterator for Trace {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(self.render());
+        }
+        if self.outcome.is_some() {
+            return None;
+        }
+        self.step();
+        match self.outcome {
+            // Exiting leaves the map as it was in the previous frame:
+            Some(Outcome::Exited) => None,
+            Some(Outcome::Looped { .. }) | None => Some(self.render()),
+        }
+    }
+}
+
+/// Writes each frame to its own numbered text file in `dir`. Returns the
+/// number of frames written.
+pub fn write_frames(
+    dir: &Path,
+    frames: impl Iterator<Item = String>,
+) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut count = 0;
+    for (i, frame) in frames.enumerate() {
+        fs::write(dir.join(format!("frame_{i:06}.txt")), frame)?;
+        count += 1;
+    }
+    Ok(count)
+}
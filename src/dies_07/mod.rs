@@ -0,0 +1,310 @@
+mod ops;
+mod solver;
+
+use std::fmt;
+
+use anyhow::anyhow;
+
+pub use ops::Fun;
+pub use solver::{Evaluation, Solver, Strategy};
+
+use crate::{
+    error::{self, ParseError, Reason},
+    Solution,
+};
+
+pub struct Data {
+    equations: Vec<Equation>,
+    strategy: Strategy,
+    evaluation: Evaluation,
+}
+
+impl Data {
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        Self { strategy, ..self }
+    }
+
+    pub fn with_evaluation(self, evaluation: Evaluation) -> Self {
+        Self { evaluation, ..self }
+    }
+
+    pub fn equations(&self) -> &[Equation] {
+        &self.equations[..]
+    }
+
+    /// Solver for `fs`, configured as this data.
+    pub fn solver(&self, fs: impl IntoIterator<Item = Fun>) -> Solver {
+        Solver::new(fs)
+            .with_strategy(self.strategy)
+            .with_evaluation(self.evaluation)
+    }
+}
+
+impl Solution for Data {
+    const DAY: u8 = 7;
+
+    type Answer1 = i64;
+    type Answer2 = i64;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut equations = Vec::new();
+        for (line_num, line) in
+            input.lines().enumerate().map(|(i, l)| (i + 1, l))
+        {
+            let equation = Equation::parse(line_num, line)?;
+            equations.push(equation);
+        }
+        Ok(Self {
+            equations,
+            strategy: Strategy::default(),
+            evaluation: Evaluation::default(),
+        })
+    }
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1> {
+        let solver = self.solver([Fun::Add, Fun::Mul]);
+        sum_of_possible_calibs(&self.equations[..], &solver)
+    }
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2> {
+        let solver = self.solver([Fun::Add, Fun::Mul, Fun::Concat]);
+        sum_of_possible_calibs(&self.equations[..], &solver)
+    }
+}
+
+fn sum_of_possible_calibs(
+    eqs: &[Equation],
+    solver: &Solver,
+) -> anyhow::Result<i64> {
+    eqs.iter()
+        .filter(|eq| eq.could_be_true(solver))
+        .try_fold(0_i64, |sum, eq| sum.checked_add(eq.calib))
+        .ok_or_else(|| anyhow!("Sum of calibration values overflows."))
+}
+
+pub struct Equation {
+    calib: i64,
+    operands: Vec<i64>,
+}
+
+/// Operators placed between an equation's operands, which make it true.
+pub struct Assignment<'a> {
+    equation: &'a Equation,
+    ops: Vec<&'a Fun>,
+}
+
+impl<'a> Assignment<'a> {
+    pub fn ops(&self) -> &[&'a Fun] {
+        &self.ops[..]
+    }
+}
+
+impl fmt::Display for Assignment<'_> {
+    /// E.g. `3267 = 81 * 40 + 27`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Equation { calib, operands } = self.equation;
+        write!(f, "{calib} =")?;
+        for (i, x) in operands.iter().enumerate() {
+            if let Some(op) = i.checked_sub(1).map(|i| self.ops[i]) {
+                write!(f, " {op}")?;
+            }
+            write!(f, " {x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Equation {
+    pub fn calib(&self) -> i64 {
+        self.calib
+    }
+
+    pub fn operands(&self) -> &[i64] {
+        &self.operands[..]
+    }
+
+    /// First operator assignment found to make the equation true.
+    pub fn solution<'a>(
+        &'a self,
+        solver: &'a Solver,
+    ) -> Option<Assignment<'a>> {
+        let ops = solver.solution(self.calib, &self.operands)?;
+        Some(Assignment {
+            equation: self,
+            ops,
+        })
+    }
+
+    /// Every operator assignment which makes the equation true.
+    pub fn solutions<'a>(
+        &'a self,
+        solver: &'a Solver,
+    ) -> Vec<Assignment<'a>> {
+        solver
+            .solutions(self.calib, &self.operands)
+            .into_iter()
+            .map(|ops| Assignment {
+                equation: self,
+                ops,
+            })
+            .collect()
+    }
+
+    pub fn count_solutions(&self, solver: &Solver) -> usize {
+        solver.count_solutions(self.calib, &self.operands)
+    }
+
+    pub fn could_be_true(&self, solver: &Solver) -> bool {
+        solver.reaches(self.calib, &self.operands)
+    }
+
+    fn parse(line_num: usize, line: &str) -> Result<Self, ParseError> {
+        let day = Data::DAY;
+        let mut fields = line.split_whitespace();
+        let calib_val = fields.next().ok_or_else(|| {
+            let reason = Reason::Missing("calibration value");
+            ParseError::new(day, line_num, 1, reason)
+        })?;
+        let calib_val = calib_val.strip_suffix(":").ok_or_else(|| {
+            let reason = Reason::MissingSeparator { separator: ":" };
+            let column = error::column(line, calib_val) + calib_val.len();
+            ParseError::new(day, line_num, column, reason)
+        })?;
+        let calib: i64 = error::parse_num(day, line_num, line, calib_val)?;
+        let mut operands: Vec<i64> = Vec::new();
+        for field in fields {
+            let operand: i64 = error::parse_num(day, line_num, line, field)?;
+            operands.push(operand);
+        }
+        Ok(Self { calib, operands })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence_on_example() {
+        let path = std::path::Path::new("tests/input/dies_07/example.txt");
+        let data = Data::load(path)
+            .unwrap()
+            .with_evaluation(Evaluation::Precedence);
+        // 292 = 11 + 6 * 16 + 20 only holds left to right:
+        assert_eq!(190 + 3267, data.part1().unwrap());
+        assert_eq!(190 + 3267 + 156, data.part2().unwrap());
+        let solver = data.solver([Fun::Add, Fun::Mul]);
+        let eq = &data.equations()[1];
+        let solution = eq.solution(&solver).unwrap();
+        assert_eq!("3267 = 81 * 40 + 27", solution.to_string());
+    }
+
+    #[test]
+    fn strategies_on_example() {
+        let path = std::path::Path::new("tests/input/dies_07/example.txt");
+        for strategy in [Strategy::Forward, Strategy::Backward] {
+            let data = Data::load(path).unwrap().with_strategy(strategy);
+            assert_eq!(3749, data.part1().unwrap());
+            assert_eq!(11387, data.part2().unwrap());
+        }
+    }
+
+    #[test]
+    fn solutions() {
+        let solver = Solver::new([Fun::Add, Fun::Mul, Fun::Concat]);
+        let fs = &solver;
+        let data = Data::parse("3267: 81 40 27\n0: 0 0\n7: 1 2").unwrap();
+        let [a, b, c] = data.equations() else {
+            unreachable!()
+        };
+        let render = |eq: &Equation| -> Vec<String> {
+            eq.solutions(fs).iter().map(|s| s.to_string()).collect()
+        };
+        assert_eq!(
+            vec!["3267 = 81 + 40 * 27", "3267 = 81 * 40 + 27"],
+            render(a)
+        );
+        assert_eq!(2, a.count_solutions(fs));
+        assert_eq!(
+            vec![&Fun::Add, &Fun::Mul],
+            a.solution(fs).unwrap().ops().to_vec()
+        );
+        // Concatenation with zero is still zero:
+        assert_eq!(vec!["0 = 0 + 0", "0 = 0 * 0", "0 = 0 || 0"], render(b));
+        assert_eq!(0, c.count_solutions(fs));
+        assert!(c.solution(fs).is_none());
+    }
+
+    #[test]
+    fn overflow() {
+        let solver = Solver::new([Fun::Add, Fun::Mul, Fun::Concat]);
+        let max = i64::MAX;
+        let data = Data::parse(&format!("{max}: {max} 1 1")).unwrap();
+        for strategy in [Strategy::Forward, Strategy::Backward] {
+            let solver = solver.clone().with_strategy(strategy);
+            assert!(data.equations[0].could_be_true(&solver));
+        }
+        assert_eq!(
+            vec!["9223372036854775807 = 9223372036854775807 * 1 * 1"],
+            data.equations[0]
+                .solutions(&solver)
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+        );
+        let data =
+            Data::parse(&format!("{max}: {max} 1\n{max}: {max} 0")).unwrap();
+        assert!(data.part1().is_err());
+    }
+
+    #[test]
+    fn strategies_agree() {
+        let fs = &[Fun::Add, Fun::Mul, Fun::Concat];
+        let forward =
+            Solver::new(fs.clone()).with_strategy(Strategy::Forward);
+        let backward = forward.clone().with_strategy(Strategy::Backward);
+        let operands = [-12, 0, 3, 7, 10, 15];
+        for a in operands {
+            for b in operands {
+                for c in operands {
+                    let equation = |calib| Equation {
+                        calib,
+                        operands: vec![a, b, c],
+                    };
+                    let totals = fs.iter().flat_map(|f| {
+                        let ab = f.apply(a, b);
+                        fs.iter().filter_map(move |g| g.apply(ab?, c))
+                    });
+                    for calib in totals.chain([-1, 0, 1, 1234]) {
+                        let eq = equation(calib);
+                        assert_eq!(
+                            eq.could_be_true(&forward),
+                            eq.could_be_true(&backward),
+                            "{calib}: {a} {b} {c}"
+                        );
+                        assert_eq!(
+                            eq.could_be_true(&forward),
+                            eq.solution(&forward).is_some(),
+                            "{calib}: {a} {b} {c}"
+                        );
+                    }
+                }
+            }
+        }
+        // Multiplying by 0 gives 0 only if the left operand doesn't
+        // overflow:
+        let eq = Equation {
+            calib: 0,
+            operands: vec![i64::MAX, i64::MAX, 0],
+        };
+        assert!(!eq.could_be_true(&forward));
+        assert!(!eq.could_be_true(&backward));
+        assert_eq!(0, forward.count_solutions(0, &eq.operands));
+        let eq = Equation {
+            calib: 0,
+            operands: vec![i64::MAX, 0, 0],
+        };
+        assert!(eq.could_be_true(&forward));
+        assert!(eq.could_be_true(&backward));
+    }
+}
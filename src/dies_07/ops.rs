@@ -0,0 +1,278 @@
+use std::{fmt, sync::Arc};
+
+/// Binary operator placed between two operands.
+///
+/// With [`Evaluation::Precedence`](super::Evaluation::Precedence), higher
+/// [`precedence`](Self::precedence) binds tighter:
+///
+/// | Operators  | Precedence       |
+/// |------------|------------------|
+/// | `**`       | 4, right to left |
+/// | `*`, `/`   | 3                |
+/// | `+`, `-`   | 2                |
+/// | `\|\|`     | 1                |
+/// | `^`        | 0                |
+#[derive(Clone, PartialEq)]
+pub enum Fun {
+    Add,
+    Sub,
+    Mul,
+    /// Integer division, rounding towards zero.
+    Div,
+    /// Exponent, for non-negative exponents only.
+    Pow,
+    /// Bitwise exclusive or.
+    Xor,
+    /// Digits of the right operand appended to the left one.
+    Concat,
+    Custom(Custom),
+}
+
+/// User-defined operator, see [`Fun::custom`].
+#[derive(Clone)]
+pub struct Custom {
+    symbol: String,
+    precedence: u8,
+    apply: Arc<dyn Fn(i64, i64) -> Option<i64> + Send + Sync>,
+}
+
+/// Left operands which could have produced a given output.
+pub(super) enum Undo {
+    Impossible,
+    /// Output is produced regardless of the left operand.
+    Any,
+    Left(i64),
+}
+
+impl Fun {
+    /// Operator computed by `apply`, which returns `None` where undefined
+    /// or on overflow.
+    pub fn custom(
+        symbol: impl Into<String>,
+        precedence: u8,
+        apply: impl Fn(i64, i64) -> Option<i64> + Send + Sync + 'static,
+    ) -> Self {
+        Self::Custom(Custom {
+            symbol: symbol.into(),
+            precedence,
+            apply: Arc::new(apply),
+        })
+    }
+
+    /// `None` when the result is undefined or overflows, which prunes the
+    /// branch of the search.
+    pub fn apply(&self, left: i64, right: i64) -> Option<i64> {
+        match self {
+            Self::Add => left.checked_add(right),
+            Self::Sub => left.checked_sub(right),
+            Self::Mul => left.checked_mul(right),
+            Self::Div => left.checked_div(right),
+            Self::Pow => left.checked_pow(u32::try_from(right).ok()?),
+            Self::Xor => Some(left ^ right),
+            Self::Concat => concat(left, right),
+            Self::Custom(custom) => (custom.apply)(left, right),
+        }
+    }
+
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Pow => 4,
+            Self::Mul | Self::Div => 3,
+            Self::Add | Self::Sub => 2,
+            Self::Concat => 1,
+            Self::Xor => 0,
+            Self::Custom(custom) => custom.precedence,
+        }
+    }
+
+    /// Whether `a op b op c` means `a op (b op c)`.
+    pub fn is_right_assoc(&self) -> bool {
+        matches!(self, Self::Pow)
+    }
+
+    /// Whether `unapply` can undo the operator.
+    pub(super) fn is_invertible(&self) -> bool {
+        self.unapply(0, 1).is_some()
+    }
+
+    /// Inverse of `apply` with respect to its left operand, or `None` for
+    /// operators without one, which would leave many left operands to try.
+    pub(super) fn unapply(&self, output: i64, right: i64) -> Option<Undo> {
+        let undo = match self {
+            Self::Add => match output.checked_sub(right) {
+                Some(left) => Undo::Left(left),
+                None => Undo::Impossible,
+            },
+            Self::Sub => match output.checked_add(right) {
+                Some(left) => Undo::Left(left),
+                None => Undo::Impossible,
+            },
+            Self::Mul if right == 0 => {
+                if output == 0 {
+                    Undo::Any
+                } else {
+                    Undo::Impossible
+                }
+            }
+            Self::Mul => match output.checked_rem(right) {
+                Some(0) => Undo::Left(output / right),
+                _ => Undo::Impossible,
+            },
+            Self::Xor => Undo::Left(output ^ right),
+            Self::Concat => {
+                let Some(shift) = concat_shift(right) else {
+                    return Some(Undo::Impossible);
+                };
+                // The sign comes from the left operand, so strip the
+                // suffix from the magnitude:
+                let magnitude = output.unsigned_abs();
+                let left = output / shift;
+                if magnitude % shift.unsigned_abs() != right.unsigned_abs()
+                    || (output < 0 && left == 0)
+                {
+                    Undo::Impossible
+                } else {
+                    Undo::Left(left)
+                }
+            }
+            Self::Div | Self::Pow | Self::Custom(_) => return None,
+        };
+        Some(undo)
+    }
+}
+
+impl fmt::Display for Fun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Pow => "**",
+            Self::Xor => "^",
+            Self::Concat => "||",
+            Self::Custom(custom) => &custom.symbol,
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+impl fmt::Debug for Fun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Custom(custom) => write!(f, "Custom({:?})", custom.symbol),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl PartialEq for Custom {
+    /// Same symbol and precedence, and the very same closure.
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol
+            && self.precedence == other.precedence
+            && Arc::ptr_eq(&self.apply, &other.apply)
+    }
+}
+
+/// Digits of `right` appended to `left`, e.g. `12 || 345 = 12345`, same as
+/// concatenating their decimal strings and parsing the result.
+fn concat(left: i64, right: i64) -> Option<i64> {
+    let shifted = left.checked_mul(concat_shift(right)?)?;
+    if left < 0 {
+        shifted.checked_sub(right)
+    } else {
+        shifted.checked_add(right)
+    }
+}
+
+/// Power of ten by which to shift the left operand of a concatenation.
+/// `None` for a negative right operand, since a minus sign in the middle
+/// does not make a number.
+fn concat_shift(right: i64) -> Option<i64> {
+    if right < 0 {
+        None
+    } else {
+        10_i64.checked_pow(digits(right))
+    }
+}
+
+fn digits(n: i64) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overflow() {
+        assert_eq!(None, Fun::Add.apply(i64::MAX, 1));
+        assert_eq!(None, Fun::Sub.apply(i64::MIN, 1));
+        assert_eq!(None, Fun::Mul.apply(i64::MAX, 2));
+        assert_eq!(None, Fun::Div.apply(i64::MIN, -1));
+        assert_eq!(None, Fun::Pow.apply(10, 19));
+        assert_eq!(None, Fun::Concat.apply(i64::MAX / 10, 8));
+        assert_eq!(None, Fun::Concat.apply(1, i64::MAX));
+        let undo = Fun::Add.unapply(i64::MIN, 1);
+        assert!(matches!(undo, Some(Undo::Impossible)));
+        let undo = Fun::Mul.unapply(i64::MIN, -1);
+        assert!(matches!(undo, Some(Undo::Impossible)));
+    }
+
+    #[test]
+    fn concat() {
+        assert_eq!(Some(12345), super::concat(12, 345));
+        assert_eq!(Some(120), super::concat(12, 0));
+        assert_eq!(Some(3), super::concat(0, 3));
+        assert_eq!(Some(-123), super::concat(-12, 3));
+        assert_eq!(None, super::concat(1, -2));
+        for (left, right) in [(12, 345), (-12, 3), (0, 7), (5, 0), (-1, 99)] {
+            let expected: i64 = format!("{left}{right}").parse().unwrap();
+            assert_eq!(Some(expected), super::concat(left, right));
+        }
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(Some(-3), Fun::Sub.apply(4, 7));
+        assert_eq!(Some(-2), Fun::Div.apply(-7, 3));
+        assert_eq!(None, Fun::Div.apply(7, 0));
+        assert_eq!(Some(1), Fun::Pow.apply(5, 0));
+        assert_eq!(None, Fun::Pow.apply(2, -1));
+        assert_eq!(Some(6), Fun::Xor.apply(5, 3));
+        let rem = Fun::custom("%", 3, |a, b| a.checked_rem(b));
+        assert_eq!(Some(1), rem.apply(7, 3));
+        assert_eq!("%", rem.to_string());
+        assert_eq!(rem, rem.clone());
+        assert_ne!(rem, Fun::custom("%", 3, |a, b| a.checked_rem(b)));
+    }
+
+    #[test]
+    fn inverses() {
+        let values = [-15, -7, -1, 0, 1, 2, 3, 10, 42];
+        for f in [Fun::Add, Fun::Sub, Fun::Mul, Fun::Xor, Fun::Concat] {
+            assert!(f.is_invertible());
+            for left in values {
+                for right in values {
+                    let Some(output) = f.apply(left, right) else {
+                        continue;
+                    };
+                    let found = match f.unapply(output, right).unwrap() {
+                        Undo::Impossible => false,
+                        Undo::Any => true,
+                        Undo::Left(undone) => undone == left,
+                    };
+                    assert!(found, "{left} {f} {right} = {output}");
+                }
+            }
+        }
+        for f in [
+            Fun::Div,
+            Fun::Pow,
+            Fun::custom("max", 0, |a, b| Some(a.max(b))),
+        ] {
+            assert!(!f.is_invertible());
+        }
+    }
+}
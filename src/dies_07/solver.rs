@@ -0,0 +1,340 @@
+use std::{cmp::Ordering, collections::HashSet, ops::ControlFlow};
+
+use super::ops::{Fun, Undo};
+
+/// How to search for operators which make an equation true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// From the first operand, expand the set of every reachable total.
+    Forward,
+    /// From the calibration value, undo each possible operator with the
+    /// last operand, pruning branches which cannot be undone. Only for
+    /// left-to-right evaluation of invertible operators, otherwise falls
+    /// back to `Forward`.
+    #[default]
+    Backward,
+}
+
+/// Order in which the operators of an expression are applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Evaluation {
+    /// Strictly left to right, as in the puzzle.
+    #[default]
+    LeftToRight,
+    /// By [`Fun::precedence`], then left to right, or right to left for
+    /// [`Fun::is_right_assoc`] operators.
+    Precedence,
+}
+
+/// Decides whether placing operators between operands can make them
+/// evaluate to a target. At least one operator must be placed, so there
+/// must be at least two operands.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    fs: Vec<Fun>,
+    evaluation: Evaluation,
+    strategy: Strategy,
+}
+
+/// Expression evaluated up to its latest operand: the left operands still
+/// waiting for operators to their right to be applied first, each with
+/// the index of its operator, and the value of the latest term.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Partial {
+    pending: Vec<(i64, usize)>,
+    last: i64,
+}
+
+impl Solver {
+    pub fn new(fs: impl IntoIterator<Item = Fun>) -> Self {
+        Self {
+            fs: fs.into_iter().collect(),
+            evaluation: Evaluation::default(),
+            strategy: Strategy::default(),
+        }
+    }
+
+    pub fn with_evaluation(self, evaluation: Evaluation) -> Self {
+        Self { evaluation, ..self }
+    }
+
+    pub fn with_strategy(self, strategy: Strategy) -> Self {
+        Self { strategy, ..self }
+    }
+
+    pub fn fs(&self) -> &[Fun] {
+        &self.fs[..]
+    }
+
+    pub fn reaches(&self, target: i64, operands: &[i64]) -> bool {
+        if operands.len() < 2 {
+            return false;
+        }
+        match self.strategy {
+            Strategy::Backward if self.is_invertible() => {
+                reaches_backward(target, operands, &self.fs)
+            }
+            _ => self.reaches_forward(target, operands),
+        }
+    }
+
+    /// First operator assignment found to reach `target`.
+    pub fn solution(
+        &self,
+        target: i64,
+        operands: &[i64],
+    ) -> Option<Vec<&Fun>> {
+        let mut found = None;
+        self.search(target, operands, &mut |ops| {
+            found = Some(ops.to_vec());
+            ControlFlow::Break(())
+        });
+        found
+    }
+
+    /// Every operator assignment which reaches `target`.
+    pub fn solutions(&self, target: i64, operands: &[i64]) -> Vec<Vec<&Fun>> {
+        let mut found = Vec::new();
+        self.search(target, operands, &mut |ops| {
+            found.push(ops.to_vec());
+            ControlFlow::Continue(())
+        });
+        found
+    }
+
+    pub fn count_solutions(&self, target: i64, operands: &[i64]) -> usize {
+        let mut count = 0;
+        self.search(target, operands, &mut |_| {
+            count += 1;
+            ControlFlow::Continue(())
+        });
+        count
+    }
+
+    fn is_invertible(&self) -> bool {
+        self.evaluation == Evaluation::LeftToRight
+            && self.fs.iter().all(Fun::is_invertible)
+    }
+
+    fn reaches_forward(&self, target: i64, operands: &[i64]) -> bool {
+        let [x1, xs @ ..] = operands else {
+            return false;
+        };
+        let mut partials = HashSet::from([Partial::new(*x1)]);
+        for &x in xs {
+            partials = partials
+                .iter()
+                .flat_map(|partial| {
+                    (0..self.fs.len())
+                        .filter_map(move |i| self.push(partial, i, x))
+                })
+                .collect();
+        }
+        partials.iter().any(|p| self.finish(p) == Some(target))
+    }
+
+    /// Exhaustive depth-first search through operator assignments, left to
+    /// right, visiting each one which reaches `target`.
+    fn search<'a>(
+        &'a self,
+        target: i64,
+        operands: &[i64],
+        visit: &mut dyn FnMut(&[&'a Fun]) -> ControlFlow<()>,
+    ) {
+        fn go<'a>(
+            solver: &'a Solver,
+            target: i64,
+            partial: &Partial,
+            rest: &[i64],
+            ops: &mut Vec<&'a Fun>,
+            visit: &mut dyn FnMut(&[&'a Fun]) -> ControlFlow<()>,
+        ) -> ControlFlow<()> {
+            match rest {
+                [] if solver.finish(partial) == Some(target) => visit(ops),
+                [] => ControlFlow::Continue(()),
+                [x, xs @ ..] => {
+                    for (i, f) in solver.fs.iter().enumerate() {
+                        if let Some(next) = solver.push(partial, i, *x) {
+                            ops.push(f);
+                            go(solver, target, &next, xs, ops, visit)?;
+                            ops.pop();
+                        }
+                    }
+                    ControlFlow::Continue(())
+                }
+            }
+        }
+
+        if let [x1, xs @ ..] = operands {
+            if !xs.is_empty() {
+                let mut ops = Vec::with_capacity(xs.len());
+                let partial = Partial::new(*x1);
+                let _ = go(self, target, &partial, xs, &mut ops, visit);
+            }
+        }
+    }
+
+    /// Extends the expression with operator `fs[i]` and operand `x`, first
+    /// applying the pending operators which go before it. `None` if any of
+    /// them fails.
+    fn push(&self, partial: &Partial, i: usize, x: i64) -> Option<Partial> {
+        let mut partial = partial.clone();
+        while let Some(&(left, j)) = partial.pending.last() {
+            if !self.goes_before(&self.fs[j], &self.fs[i]) {
+                break;
+            }
+            partial.last = self.fs[j].apply(left, partial.last)?;
+            partial.pending.pop();
+        }
+        partial.pending.push((partial.last, i));
+        partial.last = x;
+        Some(partial)
+    }
+
+    /// Value of the whole expression, applying every pending operator.
+    fn finish(&self, partial: &Partial) -> Option<i64> {
+        partial
+            .pending
+            .iter()
+            .rev()
+            .try_fold(partial.last, |right, &(left, j)| {
+                self.fs[j].apply(left, right)
+            })
+    }
+
+    /// Whether `left`, followed by `right` in the expression, is applied
+    /// before it.
+    fn goes_before(&self, left: &Fun, right: &Fun) -> bool {
+        match self.evaluation {
+            Evaluation::LeftToRight => true,
+            Evaluation::Precedence => {
+                match left.precedence().cmp(&right.precedence()) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => !right.is_right_assoc(),
+                }
+            }
+        }
+    }
+}
+
+impl Partial {
+    fn new(first: i64) -> Self {
+        Self {
+            pending: Vec::new(),
+            last: first,
+        }
+    }
+}
+
+fn reaches_backward(target: i64, operands: &[i64], fs: &[Fun]) -> bool {
+    match operands {
+        [] => false,
+        [x1] => *x1 == target,
+        [xs @ .., x] => fs.iter().any(|f| match f.unapply(target, *x) {
+            None | Some(Undo::Impossible) => false,
+            // Only as long as the left operand can be computed at all:
+            Some(Undo::Any) => evaluates(xs, fs),
+            Some(Undo::Left(left)) => reaches_backward(left, xs, fs),
+        }),
+    }
+}
+
+/// Whether some operator assignment evaluates `operands`, left to right,
+/// without overflowing.
+fn evaluates(operands: &[i64], fs: &[Fun]) -> bool {
+    fn go(left: i64, rest: &[i64], fs: &[Fun]) -> bool {
+        match rest {
+            [] => true,
+            [x, xs @ ..] => fs
+                .iter()
+                .any(|f| f.apply(left, *x).is_some_and(|y| go(y, xs, fs))),
+        }
+    }
+
+    match operands {
+        [] => false,
+        [x1, xs @ ..] => go(*x1, xs, fs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let solver = Solver::new([Fun::Add, Fun::Mul]);
+        let precedence =
+            solver.clone().with_evaluation(Evaluation::Precedence);
+        // 81 + 40 * 27 is 3267 left to right, but 1161 by precedence:
+        assert_eq!(2, solver.count_solutions(3267, &[81, 40, 27]));
+        assert_eq!(
+            vec![vec![&Fun::Mul, &Fun::Add]],
+            precedence.solutions(3267, &[81, 40, 27])
+        );
+        assert!(!solver.reaches(1161, &[81, 40, 27]));
+        assert!(precedence.reaches(1161, &[81, 40, 27]));
+        // 2 + 3 * 4 + 5 * 6:
+        assert!(precedence.reaches(44, &[2, 3, 4, 5, 6]));
+        assert!(!solver.reaches(44, &[2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn associativity() {
+        let pow = Solver::new([Fun::Pow]);
+        assert!(pow.reaches(64, &[2, 3, 2]));
+        let pow = pow.with_evaluation(Evaluation::Precedence);
+        assert!(pow.reaches(512, &[2, 3, 2]));
+        let sub =
+            Solver::new([Fun::Sub]).with_evaluation(Evaluation::Precedence);
+        assert!(sub.reaches(5, &[10, 3, 2]));
+        // 2 * 3 ** 2 - 1 ^ 4 = (2 * 9 - 1) ^ 4
+        let fs = [Fun::Mul, Fun::Pow, Fun::Sub, Fun::Xor];
+        let mixed = Solver::new(fs).with_evaluation(Evaluation::Precedence);
+        let found = mixed.solutions(17 ^ 4, &[2, 3, 2, 1, 4]);
+        assert!(found.contains(&vec![
+            &Fun::Mul,
+            &Fun::Pow,
+            &Fun::Sub,
+            &Fun::Xor
+        ]));
+    }
+
+    #[test]
+    fn custom() {
+        let max = Fun::custom("max", 5, |a, b| Some(a.max(b)));
+        let solver = Solver::new([Fun::Add, max.clone()]);
+        // (9 + 3) max 5:
+        assert!(solver.reaches(12, &[9, 3, 5]));
+        let solver = solver.with_evaluation(Evaluation::Precedence);
+        assert!(!solver.reaches(12, &[9, 3, 5]));
+        // 9 + (3 max 5), or (9 max 3) + 5:
+        assert_eq!(
+            vec![vec![&Fun::Add, &max], vec![&max, &Fun::Add]],
+            solver.solutions(14, &[9, 3, 5])
+        );
+    }
+
+    #[test]
+    fn falls_back_to_forward() {
+        let fs = [Fun::Add, Fun::Sub, Fun::Mul, Fun::Div, Fun::Concat];
+        let forward = Solver::new(fs).with_strategy(Strategy::Forward);
+        let backward = forward.clone().with_strategy(Strategy::Backward);
+        let operands = [-12, 0, 3, 7, 10, 15];
+        for a in operands {
+            for b in operands {
+                for c in operands {
+                    let xs = [a, b, c];
+                    for target in [-1, 0, 1, 10, 21, 1234, 3715] {
+                        assert_eq!(
+                            forward.reaches(target, &xs),
+                            backward.reaches(target, &xs),
+                            "{target}: {xs:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::{
+    fmt,
+    num::ParseIntError,
+    path::{Path, PathBuf},
+};
+
+/// Why and where an input failed to parse.
+///
+/// Lines and columns are 1-based. Column is a byte offset, which is the
+/// same as a character offset for the ASCII puzzle inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub day: u8,
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub reason: Reason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    BadNumber { text: String, error: ParseIntError },
+    MissingSeparator { separator: &'static str },
+    FieldCount { expected: usize, found: usize },
+    NonRectangular { expected: usize, found: usize },
+    Missing(&'static str),
+    Duplicate(&'static str),
+}
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, reason: Reason) -> Self {
+        Self {
+            day,
+            file: None,
+            line,
+            column,
+            reason,
+        }
+    }
+
+    /// Error located at `field`, which must be a sub-slice of `line`.
+    pub fn at(
+        day: u8,
+        line_num: usize,
+        line: &str,
+        field: &str,
+        reason: Reason,
+    ) -> Self {
+        Self::new(day, line_num, column(line, field), reason)
+    }
+
+    pub fn in_file(self, file: &Path) -> Self {
+        Self {
+            file: Some(file.to_path_buf()),
+            ..self
+        }
+    }
+}
+
+impl Reason {
+    pub fn bad_number(text: &str, error: ParseIntError) -> Self {
+        Self::BadNumber {
+            text: text.to_string(),
+            error,
+        }
+    }
+}
+
+/// 1-based column of `field` within `line`.
+pub fn column(line: &str, field: &str) -> usize {
+    let offset = (field.as_ptr() as usize)
+        .checked_sub(line.as_ptr() as usize)
+        .filter(|offset| *offset <= line.len())
+        .unwrap_or_else(|| panic!("{field:?} is not within {line:?}"));
+    offset + 1
+}
+
+/// Parses a number, locating the failure at `field` within `line`.
+pub fn parse_num<T: std::str::FromStr<Err = ParseIntError>>(
+    day: u8,
+    line_num: usize,
+    line: &str,
+    field: &str,
+) -> Result<T, ParseError> {
+    field.parse().map_err(|e| {
+        ParseError::at(
+            day,
+            line_num,
+            line,
+            field,
+            Reason::bad_number(field, e),
+        )
+    })
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}", file.display())?,
+            None => write!(f, "<input>")?,
+        }
+        write!(
+            f,
+            ":{}:{}: day {}: {}",
+            self.line, self.column, self.day, self.reason
+        )
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadNumber { text, error } => {
+                write!(f, "bad number {text:?}: {error}")
+            }
+            Self::MissingSeparator { separator } => {
+                write!(f, "missing separator {separator:?}")
+            }
+            Self::FieldCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            }
+            Self::NonRectangular { expected, found } => {
+                write!(
+                    f,
+                    "non-rectangular grid: expected {expected} columns, \
+                    found {found}"
+                )
+            }
+            Self::Missing(what) => write!(f, "missing {what}"),
+            Self::Duplicate(what) => write!(f, "duplicate {what}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dies_01, dies_02, dies_04, dies_05, dies_06, dies_07, Solution,
+    };
+
+    #[test]
+    fn located() {
+        let e = dies_01::Data::parse("3   4\n4   x3\n").err().unwrap();
+        assert_eq!((1, 2, 5), (e.day, e.line, e.column));
+        assert!(matches!(e.reason, Reason::BadNumber { .. }));
+
+        let e = dies_02::Data::parse("7 6 4\n\n1 2 7").err().unwrap();
+        assert_eq!((2, 1), (e.line, e.column));
+        assert_eq!(Reason::Missing("levels"), e.reason);
+
+        let e = dies_04::Data::parse("XMAS\nXMA\nXMAS\nXMAS").err().unwrap();
+        assert_eq!((2, 4), (e.line, e.column));
+        let expected = Reason::NonRectangular {
+            expected: 4,
+            found: 3,
+        };
+        assert_eq!(expected, e.reason);
+
+        let e = dies_05::Data::parse("47|53\n97,13\n").err().unwrap();
+        assert_eq!((2, 6), (e.line, e.column));
+        let expected = Reason::MissingSeparator { separator: "|" };
+        assert_eq!(expected, e.reason);
+
+        let e = dies_06::Data::parse("^.\n.>").err().unwrap();
+        assert_eq!((2, 2), (e.line, e.column));
+        assert_eq!(Reason::Duplicate("guard"), e.reason);
+
+        let e = dies_07::Data::parse("190: 10 19\n3267 81 40 27")
+            .err()
+            .unwrap();
+        assert_eq!((2, 5), (e.line, e.column));
+        let expected = Reason::MissingSeparator { separator: ":" };
+        assert_eq!(expected, e.reason);
+    }
+
+    #[test]
+    fn display() {
+        let e = ParseError::new(5, 3, 7, Reason::Missing("guard"))
+            .in_file(Path::new("input.txt"));
+        assert_eq!("input.txt:3:7: day 5: missing guard", e.to_string());
+    }
+}
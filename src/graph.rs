@@ -0,0 +1,647 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+    hash::Hash,
+};
+
+use anyhow::bail;
+
+/// Most nodes in a strongly connected component which
+/// [`Graph::feedback_arc_set`] searches exhaustively.
+pub const MAX_FEEDBACK_SEARCH: usize = 20;
+
+/// Directed graph, as the set of successors of every node. Nodes without
+/// edges are kept too.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    succs: HashMap<N, HashSet<N>>,
+}
+
+/// Nodes around a cycle, each with an edge to the next, and the last with
+/// one to the first. Displays as a path, e.g. `47 -> 53 -> 29 -> 47`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<N>(pub Vec<N>);
+
+impl<N> Default for Graph<N> {
+    fn default() -> Self {
+        Self {
+            succs: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Eq + Hash> PartialEq for Graph<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.succs == other.succs
+    }
+}
+
+impl<N: Eq + Hash> Eq for Graph<N> {}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Graph with just these edges, and the nodes at their ends.
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> Self {
+        edges.into_iter().collect()
+    }
+
+    pub fn add_node(&mut self, node: N) {
+        self.succs.entry(node).or_default();
+    }
+
+    /// Adds an edge, and either end which is not a node yet.
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_node(to.clone());
+        self.succs.entry(from).or_default().insert(to);
+    }
+
+    /// Removes an edge, keeping its ends. Returns whether it was there.
+    pub fn remove_edge(&mut self, from: &N, to: &N) -> bool {
+        self.succs
+            .get_mut(from)
+            .is_some_and(|succs| succs.remove(to))
+    }
+
+    pub fn len(&self) -> usize {
+        self.succs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.succs.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.succs.values().map(HashSet::len).sum()
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.succs.contains_key(node)
+    }
+
+    pub fn has_edge(&self, from: &N, to: &N) -> bool {
+        self.succs.get(from).is_some_and(|succs| succs.contains(to))
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.succs.keys()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.succs
+            .iter()
+            .flat_map(|(from, succs)| succs.iter().map(move |to| (from, to)))
+    }
+
+    /// Nodes with an edge from `node`, none if it is not in the graph.
+    pub fn successors(&self, node: &N) -> impl Iterator<Item = &N> {
+        self.succs.get(node).into_iter().flatten()
+    }
+
+    /// Subgraph of the nodes in `keep` which are in this graph, with every
+    /// edge between them.
+    pub fn induced<'a>(&self, keep: impl IntoIterator<Item = &'a N>) -> Self
+    where
+        N: 'a,
+    {
+        let keep: HashSet<&N> =
+            keep.into_iter().filter(|n| self.contains(n)).collect();
+        let succs = keep
+            .iter()
+            .map(|&node| {
+                let succs = self
+                    .successors(node)
+                    .filter(|succ| keep.contains(succ))
+                    .cloned()
+                    .collect();
+                (node.clone(), succs)
+            })
+            .collect();
+        Self { succs }
+    }
+
+    /// Every node, each before all of its successors, found by depth-first
+    /// search. Fails on the first cycle found.
+    // https://en.wikipedia.org/wiki/Topological_sorting#Depth-first_search
+    pub fn topo_sort(&self) -> Result<Vec<N>, Cycle<N>> {
+        let (mut sorted, cycle) = self.post_order();
+        if let Some(cycle) = cycle {
+            return Err(cycle);
+        }
+        sorted.reverse();
+        Ok(sorted.into_iter().cloned().collect())
+    }
+
+    /// Some cycle, if there are any.
+    pub fn find_cycle(&self) -> Option<Cycle<N>> {
+        self.post_order().1
+    }
+
+    /// Nodes, each after all the nodes it leads to except through a back
+    /// edge, along with the cycle closed by the first back edge.
+    fn post_order(&self) -> (Vec<&N>, Option<Cycle<N>>) {
+        let mut order = Vec::with_capacity(self.len());
+        let mut cycle = None;
+        let mut visited: HashSet<&N> = HashSet::new(); // "permanent mark"
+        let mut ancestors: HashSet<&N> = HashSet::new(); // "temporary mark"
+        for root in self.nodes() {
+            if visited.contains(root) {
+                continue;
+            }
+            // Each node on the current path, with its unvisited successors:
+            let mut path = vec![(root, self.successors(root))];
+            ancestors.insert(root);
+            while let Some((node, succs)) = path.last_mut() {
+                match succs.find(|succ| !visited.contains(succ)) {
+                    Some(succ) if ancestors.contains(succ) => {
+                        if cycle.is_none() {
+                            let nodes = path
+                                .iter()
+                                .map(|(node, _)| *node)
+                                .skip_while(|node| *node != succ)
+                                .cloned()
+                                .collect();
+                            cycle = Some(Cycle(nodes));
+                        }
+                    }
+                    Some(succ) => {
+                        ancestors.insert(succ);
+                        path.push((succ, self.successors(succ)));
+                    }
+                    None => {
+                        ancestors.remove(*node);
+                        visited.insert(*node);
+                        order.push(*node);
+                        path.pop();
+                    }
+                }
+            }
+        }
+        (order, cycle)
+    }
+
+    /// Same as [`Graph::topo_sort`], by repeatedly taking the nodes which
+    /// no remaining node has an edge to.
+    // https://en.wikipedia.org/wiki/Topological_sorting#Kahn's_algorithm
+    pub fn topo_sort_kahn(&self) -> Result<Vec<N>, Cycle<N>> {
+        self.topo_sort_by_key(|_| 0)
+    }
+
+    /// Same as [`Graph::topo_sort_kahn`], always taking the node with the
+    /// smallest key among those ready, so the order is the same every
+    /// time when no two keys are equal.
+    pub fn topo_sort_by_key<K: Ord>(
+        &self,
+        key: impl FnMut(&N) -> K,
+    ) -> Result<Vec<N>, Cycle<N>> {
+        let nodes: Vec<&N> = self.nodes().collect();
+        let index: HashMap<&N, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| (node, i))
+            .collect();
+        let keys: Vec<K> = nodes.iter().copied().map(key).collect();
+        let mut in_degrees = vec![0; nodes.len()];
+        for (_, to) in self.edges() {
+            in_degrees[index[to]] += 1;
+        }
+        let mut ready: BinaryHeap<Reverse<(&K, usize)>> = (0..nodes.len())
+            .filter(|&i| in_degrees[i] == 0)
+            .map(|i| Reverse((&keys[i], i)))
+            .collect();
+        let mut sorted = Vec::with_capacity(self.len());
+        while let Some(Reverse((_, i))) = ready.pop() {
+            sorted.push(nodes[i].clone());
+            for succ in self.successors(nodes[i]) {
+                let j = index[succ];
+                in_degrees[j] -= 1;
+                if in_degrees[j] == 0 {
+                    ready.push(Reverse((&keys[j], j)));
+                }
+            }
+        }
+        if sorted.len() < self.len() {
+            let left = (0..nodes.len())
+                .filter(|&i| in_degrees[i] > 0)
+                .map(|i| nodes[i]);
+            let cycle = self.induced(left).find_cycle();
+            return Err(cycle.expect("Only a cycle stops the sort."));
+        }
+        Ok(sorted)
+    }
+
+    /// Whether `order`, a topological order, is the only one: it is
+    /// exactly when each node has an edge to the next.
+    pub fn is_only_order(&self, order: &[N]) -> bool {
+        order
+            .windows(2)
+            .all(|pair| self.has_edge(&pair[0], &pair[1]))
+    }
+
+    /// Pairs of distinct nodes with no path between them either way, which
+    /// any topological sort may put in either order. Each pair comes once.
+    pub fn unordered_pairs(&self) -> Vec<(N, N)> {
+        let closure = self.transitive_closure();
+        let nodes: Vec<&N> = self.nodes().collect();
+        let mut pairs = Vec::new();
+        for (i, &a) in nodes.iter().enumerate() {
+            for &b in &nodes[i + 1..] {
+                if !closure.has_edge(a, b) && !closure.has_edge(b, a) {
+                    pairs.push((a.clone(), b.clone()));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Nodes at the end of a path of one or more edges from `from`, which
+    /// includes `from` itself only if it is on a cycle.
+    pub fn reachable(&self, from: &N) -> HashSet<N> {
+        let mut seen: HashSet<&N> = HashSet::new();
+        let mut todo: Vec<&N> = self.successors(from).collect();
+        while let Some(node) = todo.pop() {
+            if seen.insert(node) {
+                todo.extend(self.successors(node));
+            }
+        }
+        seen.into_iter().cloned().collect()
+    }
+
+    pub fn is_reachable(&self, from: &N, to: &N) -> bool {
+        self.reachable(from).contains(to)
+    }
+
+    /// Graph with an edge wherever there is a path in this one.
+    pub fn transitive_closure(&self) -> Self {
+        let succs = self
+            .nodes()
+            .map(|node| (node.clone(), self.reachable(node)))
+            .collect();
+        Self { succs }
+    }
+
+    /// Fewest edges with the same paths as this graph, which is unique for
+    /// an acyclic graph. Fails if there is a cycle.
+    pub fn transitive_reduction(&self) -> Result<Self, Cycle<N>> {
+        self.topo_sort()?;
+        let closure = self.transitive_closure();
+        let succs = self
+            .succs
+            .iter()
+            .map(|(node, succs)| {
+                // An edge is redundant if another path leads to its end:
+                let direct = succs
+                    .iter()
+                    .filter(|&to| {
+                        !succs
+                            .iter()
+                            .any(|via| via != to && closure.has_edge(via, to))
+                    })
+                    .cloned()
+                    .collect();
+                (node.clone(), direct)
+            })
+            .collect();
+        Ok(Self { succs })
+    }
+
+    /// Same graph, with every edge turned around.
+    pub fn reversed(&self) -> Self {
+        let mut g = Self::new();
+        for node in self.nodes() {
+            g.add_node(node.clone());
+        }
+        g.extend(self.edges().map(|(from, to)| (to.clone(), from.clone())));
+        g
+    }
+
+    /// Groups of nodes which all lead to each other, each before the
+    /// groups it leads to.
+    // https://en.wikipedia.org/wiki/Kosaraju%27s_algorithm
+    pub fn strongly_connected_components(&self) -> Vec<Vec<N>> {
+        let (mut order, _) = self.post_order();
+        order.reverse();
+        let reversed = self.reversed();
+        let mut assigned: HashSet<&N> = HashSet::new();
+        let mut components = Vec::new();
+        for root in order {
+            if !assigned.insert(root) {
+                continue;
+            }
+            let mut component = vec![root.clone()];
+            let mut todo = vec![root];
+            while let Some(node) = todo.pop() {
+                for pred in reversed.successors(node) {
+                    // Borrowed from this graph, to outlive `reversed`:
+                    let Some((pred, _)) = self.succs.get_key_value(pred)
+                    else {
+                        continue;
+                    };
+                    if assigned.insert(pred) {
+                        component.push(pred.clone());
+                        todo.push(pred);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// A smallest set of edges whose removal leaves no cycle. Edges
+    /// between strongly connected components are never needed, and the
+    /// edges within each are found by exhaustive search, which fails on
+    /// a component of more than [`MAX_FEEDBACK_SEARCH`] nodes.
+    // https://en.wikipedia.org/wiki/Feedback_arc_set
+    pub fn feedback_arc_set(&self) -> anyhow::Result<Vec<(N, N)>> {
+        self.feedback_arc_set_by_key(|_| 0)
+    }
+
+    /// Same as [`Graph::feedback_arc_set`], breaking ties between equally
+    /// small sets by the keys of the nodes, and listing edges by the keys
+    /// of their ends, so the result is the same every time when no two
+    /// keys are equal.
+    pub fn feedback_arc_set_by_key<K: Ord>(
+        &self,
+        mut key: impl FnMut(&N) -> K,
+    ) -> anyhow::Result<Vec<(N, N)>> {
+        let mut arcs = Vec::new();
+        for mut component in self.strongly_connected_components() {
+            component.sort_by_cached_key(&mut key);
+            let n = component.len();
+            if n > MAX_FEEDBACK_SEARCH {
+                bail!(
+                    "Cycles through {n} nodes are too many to search, \
+                    at most {MAX_FEEDBACK_SEARCH}."
+                );
+            }
+            // Each node's successors within the component, as bits:
+            let succs: Vec<u32> = component
+                .iter()
+                .map(|node| {
+                    component
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, succ)| self.has_edge(node, succ))
+                        .fold(0, |bits, (i, _)| bits | 1 << i)
+                })
+                .collect();
+            // Fewest edges pointing backwards in any order of the nodes in
+            // each subset, when that subset comes first, and the last node
+            // of such an order:
+            let mut fewest = vec![0u32; 1 << n];
+            let mut last = vec![0u8; 1 << n];
+            for set in 1usize..1 << n {
+                let (cost, node) = (0..n)
+                    .filter(|i| set & 1 << i != 0)
+                    .map(|i| {
+                        let before = set & !(1 << i);
+                        let back = (succs[i] as usize & set).count_ones();
+                        (fewest[before] + back, i)
+                    })
+                    .min()
+                    .unwrap_or_default();
+                fewest[set] = cost;
+                last[set] = node as u8;
+            }
+            let mut order = Vec::with_capacity(n);
+            let mut set = (1usize << n) - 1;
+            while set != 0 {
+                let node = usize::from(last[set]);
+                order.push(node);
+                set &= !(1 << node);
+            }
+            order.reverse();
+            for (at, &i) in order.iter().enumerate() {
+                for &j in &order[..=at] {
+                    if succs[i] & 1 << j != 0 {
+                        arcs.push((
+                            component[i].clone(),
+                            component[j].clone(),
+                        ));
+                    }
+                }
+            }
+        }
+        arcs.sort_by_cached_key(|(from, to)| (key(from), key(to)));
+        Ok(arcs)
+    }
+
+    /// Same graph, without these edges.
+    pub fn without_edges<'a>(
+        &self,
+        edges: impl IntoIterator<Item = &'a (N, N)>,
+    ) -> Self
+    where
+        N: 'a,
+    {
+        let mut g = self.clone();
+        for (from, to) in edges {
+            g.remove_edge(from, to);
+        }
+        g
+    }
+}
+
+impl<N: Clone + Eq + Hash> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut g = Self::new();
+        g.extend(edges);
+        g
+    }
+}
+
+impl<N: Clone + Eq + Hash> Extend<(N, N)> for Graph<N> {
+    fn extend<I: IntoIterator<Item = (N, N)>>(&mut self, edges: I) {
+        for (from, to) in edges {
+            self.add_edge(from, to);
+        }
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Cycle<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in &self.0 {
+            write!(f, "{node} -> ")?;
+        }
+        match self.0.first() {
+            Some(first) => write!(f, "{first}"),
+            None => write!(f, "()"),
+        }
+    }
+}
+
+impl<N: fmt::Debug + fmt::Display> std::error::Error for Cycle<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted<'a>(nodes: impl IntoIterator<Item = &'a char>) -> String {
+        let mut nodes: Vec<char> = nodes.into_iter().copied().collect();
+        nodes.sort();
+        nodes.into_iter().collect()
+    }
+
+    fn edges(g: &Graph<char>) -> Vec<String> {
+        let mut edges: Vec<String> =
+            g.edges().map(|(a, b)| format!("{a}{b}")).collect();
+        edges.sort();
+        edges
+    }
+
+    /// Edges like `ab bc`.
+    fn graph(edges: &str) -> Graph<char> {
+        edges
+            .split_whitespace()
+            .map(|e| {
+                let mut e = e.chars();
+                (e.next().unwrap(), e.next().unwrap())
+            })
+            .collect()
+    }
+
+    fn assert_topo(g: &Graph<char>, order: &[char]) {
+        assert_eq!(g.len(), order.len());
+        let at = |n: &char| order.iter().position(|m| m == n).unwrap();
+        for (from, to) in g.edges() {
+            assert!(at(from) < at(to), "{from}{to} in {order:?}");
+        }
+    }
+
+    #[test]
+    fn build() {
+        let mut g = graph("ab bc ac");
+        g.add_node('z');
+        g.add_edge('a', 'b');
+        assert_eq!((4, 3), (g.len(), g.edge_count()));
+        assert_eq!("abcz", sorted(g.nodes()));
+        assert_eq!("bc", sorted(g.successors(&'a')));
+        assert_eq!("", sorted(g.successors(&'y')));
+        assert!(g.has_edge(&'a', &'c') && !g.has_edge(&'c', &'a'));
+        let mut h = Graph::from_edges([('a', 'c'), ('b', 'c'), ('a', 'b')]);
+        assert_ne!(g, h);
+        h.add_node('z');
+        assert_eq!(g, h);
+    }
+
+    #[test]
+    fn induced() {
+        let g = graph("ab bc cd da ac");
+        let sub = g.induced(&['a', 'c', 'd', 'x']);
+        assert_eq!("acd", sorted(sub.nodes()));
+        assert_eq!(vec!["ac", "cd", "da"], edges(&sub));
+    }
+
+    #[test]
+    fn topo_sort() {
+        let g = graph("ab bc ac de ec fz");
+        assert_topo(&g, &g.topo_sort().unwrap());
+        assert_topo(&g, &g.topo_sort_kahn().unwrap());
+        let g = graph("ab bc cd db de");
+        assert_cycle(&g, "bcd", &g.topo_sort().unwrap_err());
+        assert_cycle(&g, "bcd", &g.topo_sort_kahn().unwrap_err());
+        let g = graph("aa");
+        assert_eq!("a -> a", g.topo_sort().unwrap_err().to_string());
+        assert_eq!(Vec::<char>::new(), Graph::new().topo_sort().unwrap());
+    }
+
+    #[test]
+    fn ordered_by_key() {
+        let g = graph("ac bc cd ed");
+        let order = |key: fn(&char) -> i32| -> String {
+            g.topo_sort_by_key(key).unwrap().into_iter().collect()
+        };
+        assert_eq!("abced", order(|c| *c as i32));
+        assert_eq!("ebacd", order(|c| -(*c as i32)));
+        assert!(!g.is_only_order(&['a', 'b', 'c', 'e', 'd']));
+        let mut pairs: Vec<String> = g
+            .unordered_pairs()
+            .into_iter()
+            .map(|(a, b)| sorted(&[a, b]))
+            .collect();
+        pairs.sort();
+        assert_eq!(vec!["ab", "ae", "be", "ce"], pairs);
+        let g = graph("ab bc ac");
+        assert!(g.is_only_order(&g.topo_sort().unwrap()));
+        assert!(g.unordered_pairs().is_empty());
+        let g = graph("ab ba");
+        assert_eq!(
+            vec![('a', 'b')],
+            g.feedback_arc_set_by_key(|c| *c).unwrap()
+        );
+        let arcs = g.feedback_arc_set_by_key(|c| Reverse(*c)).unwrap();
+        assert_eq!(vec![('b', 'a')], arcs);
+    }
+
+    /// `cycle` goes through the nodes in `nodes`, along edges of `g`.
+    fn assert_cycle(g: &Graph<char>, nodes: &str, cycle: &Cycle<char>) {
+        assert_eq!(nodes, sorted(&cycle.0));
+        let next = cycle.0.iter().cycle().skip(1);
+        for (from, to) in cycle.0.iter().zip(next) {
+            assert!(g.has_edge(from, to), "{cycle}");
+        }
+        let text = cycle.to_string();
+        assert_eq!(nodes.len() + 1, text.split(" -> ").count());
+    }
+
+    #[test]
+    fn components() {
+        let g = graph("ab ba bc cd dc de ff");
+        let components: Vec<String> = g
+            .strongly_connected_components()
+            .iter()
+            .map(sorted)
+            .collect();
+        let at = |c: &str| components.iter().position(|d| d == c).unwrap();
+        assert!(at("ab") < at("cd") && at("cd") < at("e"));
+        assert_eq!(4, components.len());
+        assert!(components.contains(&"f".to_string()));
+    }
+
+    #[test]
+    fn feedback_arc_set() {
+        let g = graph("ab bc ca cd de ec ff");
+        let arcs = g.feedback_arc_set().unwrap();
+        assert_eq!(3, arcs.len());
+        assert!(arcs.contains(&('f', 'f')));
+        let acyclic = g.without_edges(&arcs);
+        assert_topo(&acyclic, &acyclic.topo_sort().unwrap());
+        assert!(graph("ab ac ad bc bd cd")
+            .feedback_arc_set()
+            .unwrap()
+            .is_empty());
+        // A total order with one edge turned around:
+        let arcs = graph("ab ac bc bd cd da").feedback_arc_set().unwrap();
+        assert_eq!(vec![('d', 'a')], arcs);
+        let n = MAX_FEEDBACK_SEARCH + 1;
+        let ring: Graph<usize> = (0..n).map(|i| (i, (i + 1) % n)).collect();
+        assert!(ring.feedback_arc_set().is_err());
+    }
+
+    #[test]
+    fn reachable() {
+        let g = graph("ab bc cb cd ed");
+        assert_eq!("bcd", sorted(&g.reachable(&'a')));
+        assert_eq!("bcd", sorted(&g.reachable(&'b')));
+        assert_eq!("", sorted(&g.reachable(&'d')));
+        assert!(g.is_reachable(&'e', &'d') && !g.is_reachable(&'d', &'e'));
+        let closure = g.transitive_closure();
+        assert_eq!(
+            vec!["ab", "ac", "ad", "bb", "bc", "bd", "cb", "cc", "cd", "ed"],
+            edges(&closure)
+        );
+    }
+
+    #[test]
+    fn transitive_reduction() {
+        let g = graph("ab bc ac cd ad bd ae");
+        let reduced = g.transitive_reduction().unwrap();
+        assert_eq!(vec!["ab", "ae", "bc", "cd"], edges(&reduced));
+        assert_eq!(g.transitive_closure(), reduced.transitive_closure());
+        assert!(graph("ab ba").transitive_reduction().is_err());
+    }
+}
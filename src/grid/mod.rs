@@ -0,0 +1,426 @@
+mod pattern;
+mod search;
+
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+pub use pattern::{Pattern, Placement, Transform};
+pub use search::{Match, WordSearch};
+
+use crate::error::{ParseError, Reason};
+
+/// Cell coordinates: row and column, from the top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub r: usize,
+    pub k: usize,
+}
+
+/// Signed step between cells, in rows and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Offset {
+    pub r: isize,
+    pub k: isize,
+}
+
+impl Offset {
+    pub const N: Self = Self::new(-1, 0);
+    pub const NE: Self = Self::new(-1, 1);
+    pub const E: Self = Self::new(0, 1);
+    pub const SE: Self = Self::new(1, 1);
+    pub const S: Self = Self::new(1, 0);
+    pub const SW: Self = Self::new(1, -1);
+    pub const W: Self = Self::new(0, -1);
+    pub const NW: Self = Self::new(-1, -1);
+
+    /// Orthogonal directions, clockwise from north.
+    pub const DIRS_4: [Self; 4] = [Self::N, Self::E, Self::S, Self::W];
+
+    /// Orthogonal and diagonal directions, clockwise from north.
+    pub const DIRS_8: [Self; 8] = [
+        Self::N,
+        Self::NE,
+        Self::E,
+        Self::SE,
+        Self::S,
+        Self::SW,
+        Self::W,
+        Self::NW,
+    ];
+
+    pub const fn new(r: isize, k: isize) -> Self {
+        Self { r, k }
+    }
+
+    pub fn scale(self, n: isize) -> Self {
+        Self::new(self.r * n, self.k * n)
+    }
+
+    pub fn rev(self) -> Self {
+        self.scale(-1)
+    }
+}
+
+/// Rectangular 2D map, stored row-major in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(rows: usize, cols: usize, cells: Vec<T>) -> Self {
+        assert_eq!(rows * cols, cells.len(), "Cell count must fit shape.");
+        Self { rows, cols, cells }
+    }
+
+    pub fn from_fn(
+        rows: usize,
+        cols: usize,
+        mut cell: impl FnMut(Pos) -> T,
+    ) -> Self {
+        let cells = (0..rows)
+            .flat_map(|r| (0..cols).map(move |k| Pos { r, k }))
+            .map(&mut cell)
+            .collect();
+        Self { rows, cols, cells }
+    }
+
+    /// One row per line, one cell per character, each mapped by `cell`.
+    /// Lines of different lengths are an error attributed to `day`.
+    pub fn parse(
+        day: u8,
+        input: &str,
+        mut cell: impl FnMut(Pos, char) -> T,
+    ) -> Result<Self, ParseError> {
+        let mut cells = Vec::new();
+        let mut rows = 0;
+        let mut cols = None;
+        for (r, line) in input.lines().enumerate() {
+            let before = cells.len();
+            cells.extend(
+                line.chars().enumerate().map(|(k, c)| cell(Pos { r, k }, c)),
+            );
+            let found = cells.len() - before;
+            let expected = *cols.get_or_insert(found);
+            if found != expected {
+                let reason = Reason::NonRectangular { expected, found };
+                let column = expected.min(found) + 1;
+                return Err(ParseError::new(day, r + 1, column, reason));
+            }
+            rows += 1;
+        }
+        let cols = cols.unwrap_or(0);
+        Ok(Self { rows, cols, cells })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        pos.r < self.rows && pos.k < self.cols
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[self.flat(pos)])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        if !self.contains(pos) {
+            return None;
+        }
+        let i = self.flat(pos);
+        Some(&mut self.cells[i])
+    }
+
+    /// Position `offset` away from `pos`, if still within bounds.
+    pub fn step(&self, pos: Pos, offset: Offset) -> Option<Pos> {
+        let r = pos.r.checked_add_signed(offset.r)?;
+        let k = pos.k.checked_add_signed(offset.k)?;
+        let pos = Pos { r, k };
+        self.contains(pos).then_some(pos)
+    }
+
+    /// Position `offset` away from `pos`, wrapping around the edges, as if
+    /// the grid were tiled in every direction.
+    ///
+    /// # Panics
+    ///
+    /// If the grid is empty, having no position to wrap to.
+    pub fn wrap(&self, pos: Pos, offset: Offset) -> Pos {
+        assert!(self.rows > 0 && self.cols > 0, "Cannot wrap an empty grid.");
+        let wrap = |i: usize, d: isize, n: usize| {
+            (i as isize + d).rem_euclid(n as isize) as usize
+        };
+        Pos {
+            r: wrap(pos.r, offset.r, self.rows),
+            k: wrap(pos.k, offset.k, self.cols),
+        }
+    }
+
+    pub fn neighbor(&self, pos: Pos, offset: Offset) -> Option<(Pos, &T)> {
+        let pos = self.step(pos, offset)?;
+        Some((pos, &self[pos]))
+    }
+
+    /// In-bounds orthogonal neighbors, clockwise from north.
+    pub fn neighbors4(
+        &self,
+        pos: Pos,
+    ) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        Offset::DIRS_4
+            .into_iter()
+            .filter_map(move |o| self.neighbor(pos, o))
+    }
+
+    /// In-bounds orthogonal and diagonal neighbors, clockwise from north.
+    pub fn neighbors8(
+        &self,
+        pos: Pos,
+    ) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        Offset::DIRS_8
+            .into_iter()
+            .filter_map(move |o| self.neighbor(pos, o))
+    }
+
+    /// Cells from `pos` (inclusive), repeatedly stepping by `offset`,
+    /// until falling off the grid.
+    pub fn ray(
+        &self,
+        pos: Pos,
+        offset: Offset,
+    ) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        std::iter::successors(self.contains(pos).then_some(pos), move |pos| {
+            self.step(*pos, offset)
+        })
+        .map(|pos| (pos, &self[pos]))
+    }
+
+    /// Every maximal line of cells in the `offset` direction: rows for
+    /// `E`, columns for `S`, diagonals for `SE`, anti-diagonals for `SW`.
+    pub fn lines(
+        &self,
+        offset: Offset,
+    ) -> impl Iterator<Item = impl Iterator<Item = (Pos, &T)> + '_> + '_ {
+        self.positions()
+            .filter(move |pos| self.step(*pos, offset.rev()).is_none())
+            .map(move |pos| self.ray(pos, offset))
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.cells[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn column(&self, k: usize) -> impl Iterator<Item = &T> + '_ {
+        self.ray(Pos { r: 0, k }, Offset::S).map(|(_, cell)| cell)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.rows).flat_map(|r| (0..self.cols).map(move |k| Pos { r, k }))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Renders one line per row, one character per cell.
+    pub fn render(&self, mut cell: impl FnMut(Pos, &T) -> char) -> String {
+        let mut out = String::with_capacity(self.rows * (self.cols + 1));
+        for (pos, c) in self.iter() {
+            out.push(cell(pos, c));
+            if pos.k + 1 == self.cols {
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    fn flat(&self, pos: Pos) -> usize {
+        pos.r * self.cols + pos.k
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(rows: usize, cols: usize, cell: T) -> Self {
+        Self::new(rows, cols, vec![cell; rows * cols])
+    }
+
+    /// Rows become columns.
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.cols, self.rows, |Pos { r, k }| {
+            self[Pos { r: k, k: r }].clone()
+        })
+    }
+
+    /// Quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        Self::from_fn(self.cols, self.rows, |Pos { r, k }| {
+            self[Pos {
+                r: self.rows - 1 - k,
+                k: r,
+            }]
+            .clone()
+        })
+    }
+
+    /// Quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        Self::from_fn(self.cols, self.rows, |Pos { r, k }| {
+            self[Pos {
+                r: k,
+                k: self.cols - 1 - r,
+            }]
+            .clone()
+        })
+    }
+
+    /// Mirror left-to-right.
+    pub fn flip_horizontal(&self) -> Self {
+        Self::from_fn(self.rows, self.cols, |Pos { r, k }| {
+            self[Pos {
+                r,
+                k: self.cols - 1 - k,
+            }]
+            .clone()
+        })
+    }
+
+    /// Mirror top-to-bottom.
+    pub fn flip_vertical(&self) -> Self {
+        Self::from_fn(self.rows, self.cols, |Pos { r, k }| {
+            self[Pos {
+                r: self.rows - 1 - r,
+                k,
+            }]
+            .clone()
+        })
+    }
+}
+
+impl Grid<char> {
+    /// Renders the grid with every cell not among `cells` blanked out as
+    /// `.`, e.g. to show where words or patterns were found.
+    pub fn highlight(&self, cells: impl IntoIterator<Item = Pos>) -> String {
+        let mut shown = Grid::filled(self.rows, self.cols, false);
+        for pos in cells {
+            shown[pos] = true;
+        }
+        self.render(|pos, c| if shown[pos] { *c } else { '.' })
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &Self::Output {
+        assert!(self.contains(pos), "{pos:?} out of bounds.");
+        &self.cells[self.flat(pos)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut Self::Output {
+        assert!(self.contains(pos), "{pos:?} out of bounds.");
+        let i = self.flat(pos);
+        &mut self.cells[i]
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.rows {
+            for cell in self.row(r) {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn grid(input: &str) -> Grid<char> {
+        Grid::parse(0, input, |_, c| c).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let mut g = grid("abc\ndef");
+        assert_eq!((2, 3), (g.rows(), g.cols()));
+        assert_eq!('f', g[Pos { r: 1, k: 2 }]);
+        assert_eq!("abc\ndef\n", g.to_string());
+        let far = Pos {
+            r: usize::MAX,
+            k: usize::MAX,
+        };
+        assert_eq!(None, g.get(far));
+        assert_eq!(None, g.get_mut(far));
+        assert_eq!(None, g.get_mut(Pos { r: 0, k: 3 }));
+        let e = Grid::parse(0, "abc\nde", |_, c| c).unwrap_err();
+        assert_eq!((2, 3), (e.line, e.column));
+    }
+
+    #[test]
+    fn neighbors() {
+        let g = grid("abc\ndef\nghi");
+        let corner: String =
+            g.neighbors8(Pos { r: 0, k: 0 }).map(|(_, c)| *c).collect();
+        assert_eq!("bed", corner);
+        let center: String =
+            g.neighbors4(Pos { r: 1, k: 1 }).map(|(_, c)| *c).collect();
+        assert_eq!("bfhd", center);
+        assert_eq!(None, g.neighbor(Pos { r: 2, k: 2 }, Offset::SE));
+        let corner = Pos { r: 2, k: 2 };
+        assert_eq!(Pos { r: 0, k: 0 }, g.wrap(corner, Offset::SE));
+        assert_eq!(Pos { r: 2, k: 1 }, g.wrap(corner, Offset::new(3, -4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot wrap an empty grid.")]
+    fn wrap_empty() {
+        grid("").wrap(Pos { r: 0, k: 0 }, Offset::E);
+    }
+
+    #[test]
+    fn lines() {
+        let g = grid("abc\ndef");
+        let lines = |o| -> Vec<String> {
+            g.lines(o).map(|l| l.map(|(_, c)| *c).collect()).collect()
+        };
+        assert_eq!(vec!["abc", "def"], lines(Offset::E));
+        assert_eq!(vec!["ad", "be", "cf"], lines(Offset::S));
+        assert_eq!(vec!["ae", "bf", "c", "d"], lines(Offset::SE));
+        assert_eq!(vec!["a", "bd", "ce", "f"], lines(Offset::SW));
+        assert_eq!(vec!['b', 'e'], g.column(1).copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn transform() {
+        let g = grid("abc\ndef");
+        assert_eq!("ad\nbe\ncf\n", g.transpose().to_string());
+        assert_eq!("da\neb\nfc\n", g.rotate_cw().to_string());
+        assert_eq!("cf\nbe\nad\n", g.rotate_ccw().to_string());
+        assert_eq!("cba\nfed\n", g.flip_horizontal().to_string());
+        assert_eq!("def\nabc\n", g.flip_vertical().to_string());
+        assert_eq!(g, g.rotate_cw().rotate_ccw());
+    }
+}
@@ -0,0 +1,269 @@
+use std::{iter, str::FromStr};
+
+use anyhow::bail;
+
+use super::{Grid, Pos};
+
+/// One of the eight rotations and reflections of a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    /// Mirror along the main diagonal.
+    Transpose,
+    /// Mirror along the other diagonal.
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ROTATIONS: [Self; 4] = [
+        Self::Identity,
+        Self::RotateCw,
+        Self::Rotate180,
+        Self::RotateCcw,
+    ];
+
+    /// Rotations and reflections.
+    pub const ALL: [Self; 8] = [
+        Self::Identity,
+        Self::RotateCw,
+        Self::Rotate180,
+        Self::RotateCcw,
+        Self::FlipHorizontal,
+        Self::FlipVertical,
+        Self::Transpose,
+        Self::AntiTranspose,
+    ];
+
+    pub fn apply<T: Clone>(self, grid: &Grid<T>) -> Grid<T> {
+        match self {
+            Self::Identity => grid.clone(),
+            Self::RotateCw => grid.rotate_cw(),
+            Self::Rotate180 => grid.flip_horizontal().flip_vertical(),
+            Self::RotateCcw => grid.rotate_ccw(),
+            Self::FlipHorizontal => grid.flip_horizontal(),
+            Self::FlipVertical => grid.flip_vertical(),
+            Self::Transpose => grid.transpose(),
+            Self::AntiTranspose => grid.rotate_cw().flip_vertical(),
+        }
+    }
+}
+
+/// Small template to find within a character grid, where `None` cells
+/// match anything.
+///
+/// Parses from rows separated by `/` or newlines, with `.` as the
+/// wildcard, e.g. `M.S/.A./M.S`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    template: Grid<Option<char>>,
+    transforms: Vec<Transform>,
+}
+
+/// Where a pattern was found: the top-left corner of its transformed
+/// template, and the cells matching the template's non-wildcard ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Placement {
+    pub start: Pos,
+    pub transform: Transform,
+    pub cells: Vec<Pos>,
+}
+
+impl Pattern {
+    pub fn new(template: Grid<Option<char>>) -> Self {
+        Self {
+            template,
+            transforms: vec![Transform::Identity],
+        }
+    }
+
+    /// Also looks for the template transformed in each of these ways,
+    /// besides as it is. Transforms which produce the same template as an
+    /// earlier one are skipped, so a symmetric shape is found once per
+    /// occurrence.
+    pub fn with_transforms(
+        self,
+        transforms: impl IntoIterator<Item = Transform>,
+    ) -> Self {
+        Self {
+            transforms: iter::once(Transform::Identity)
+                .chain(transforms)
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Distinct transformed templates, each with the first transform
+    /// producing it.
+    pub fn variants(&self) -> Vec<(Transform, Grid<Option<char>>)> {
+        let mut variants: Vec<(Transform, Grid<Option<char>>)> = Vec::new();
+        for &transform in &self.transforms {
+            let variant = transform.apply(&self.template);
+            if variants.iter().all(|(_, v)| *v != variant) {
+                variants.push((transform, variant));
+            }
+        }
+        variants
+    }
+
+    /// Every placement, ordered by position, then transform.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<Placement> {
+        let variants = self.variants();
+        let mut found = Vec::new();
+        for start in grid.positions() {
+            for (transform, variant) in &variants {
+                if let Some(cells) = fit(grid, start, variant) {
+                    found.push(Placement {
+                        start,
+                        transform: *transform,
+                        cells,
+                    });
+                }
+            }
+        }
+        found
+    }
+
+    pub fn count(&self, grid: &Grid<char>) -> usize {
+        self.find(grid).len()
+    }
+}
+
+/// Cells matched by `template` with its top-left corner at `start`, if it
+/// fits there.
+fn fit(
+    grid: &Grid<char>,
+    start: Pos,
+    template: &Grid<Option<char>>,
+) -> Option<Vec<Pos>> {
+    let fits = start.r + template.rows() <= grid.rows()
+        && start.k + template.cols() <= grid.cols();
+    if !fits {
+        return None;
+    }
+    let mut cells = Vec::new();
+    for (Pos { r, k }, expected) in template.iter() {
+        let Some(expected) = expected else {
+            continue;
+        };
+        let pos = Pos {
+            r: start.r + r,
+            k: start.k + k,
+        };
+        if grid[pos] != *expected {
+            return None;
+        }
+        cells.push(pos);
+    }
+    Some(cells)
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows: Vec<&str> = s
+            .trim_end()
+            .split(['/', '\n'])
+            .map(|row| row.trim_end_matches('\r'))
+            .collect();
+        let cols = rows[0].chars().count();
+        if cols == 0 {
+            bail!("Empty pattern: {s:?}");
+        }
+        if let Some(row) = rows.iter().find(|r| r.chars().count() != cols) {
+            bail!("Non-rectangular pattern: {row:?} is not {cols} wide.");
+        }
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| (c != '.').then_some(c))
+            .collect();
+        Ok(Self::new(Grid::new(rows.len(), cols, cells)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::tests::grid;
+
+    #[test]
+    fn transforms() {
+        let g = grid("ab\ncd");
+        let all: Vec<String> = Transform::ALL
+            .iter()
+            .map(|t| t.apply(&g).to_string())
+            .collect();
+        let expected = [
+            "ab\ncd\n", "ca\ndb\n", "dc\nba\n", "bd\nac\n", "ba\ndc\n",
+            "cd\nab\n", "ac\nbd\n", "db\nca\n",
+        ];
+        assert_eq!(&expected[..], all);
+    }
+
+    #[test]
+    fn symmetric() {
+        let x_mas: Pattern = "M.S/.A./M.S".parse().unwrap();
+        assert_eq!(1, x_mas.variants().len());
+        let x_mas = x_mas.with_transforms(Transform::ALL);
+        let variants: Vec<Transform> =
+            x_mas.variants().into_iter().map(|(t, _)| t).collect();
+        let expected = [
+            Transform::Identity,
+            Transform::RotateCw,
+            Transform::Rotate180,
+            Transform::RotateCcw,
+        ];
+        assert_eq!(&expected[..], variants);
+        // The template as it is always comes first:
+        let variants: Vec<Transform> = x_mas
+            .with_transforms([Transform::RotateCw])
+            .variants()
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(&expected[..2], variants);
+    }
+
+    #[test]
+    fn find() {
+        let g = grid("MAS.\nAMSS\nSA.S\n..MA");
+        let plus: Pattern = ".A./AMS/.A.".parse().unwrap();
+        let found = plus.with_transforms(Transform::ALL).find(&g);
+        assert_eq!(1, found.len());
+        let Placement {
+            start,
+            transform,
+            cells,
+        } = &found[0];
+        assert_eq!(
+            (Pos { r: 0, k: 0 }, Transform::Identity),
+            (*start, *transform)
+        );
+        assert_eq!(
+            ".A..\nAMS.\n.A..\n....\n",
+            g.highlight(cells.iter().copied())
+        );
+        // An L shape, flipped:
+        let l: Pattern = "S./S./AM".parse().unwrap();
+        assert_eq!(0, l.count(&g));
+        let found = l.with_transforms(Transform::ALL).find(&g);
+        let found: Vec<(Pos, Transform)> =
+            found.iter().map(|p| (p.start, p.transform)).collect();
+        let expected = [(Pos { r: 1, k: 2 }, Transform::FlipHorizontal)];
+        assert_eq!(&expected[..], found);
+    }
+
+    #[test]
+    fn parse() {
+        let p: Pattern = "ab\n.c\n".parse().unwrap();
+        assert_eq!(p, "ab/.c".parse().unwrap());
+        assert!("ab/c".parse::<Pattern>().is_err());
+        assert!("".parse::<Pattern>().is_err());
+    }
+}
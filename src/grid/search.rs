@@ -0,0 +1,155 @@
+use super::{Grid, Offset, Pos};
+
+/// Finds words spelled out in straight lines of a character grid.
+///
+/// By default a word may read in any of the eight directions and must fit
+/// within the grid. A word never uses the same cell twice, so with
+/// wrap-around it is at most as long as the line it loops along.
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    words: Vec<Vec<char>>,
+    dirs: Vec<Offset>,
+    wrap: bool,
+}
+
+/// Where a word was found, along with every cell it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// Index of the word, in the order the search was given them.
+    pub word: usize,
+    pub start: Pos,
+    pub dir: Offset,
+    pub cells: Vec<Pos>,
+}
+
+impl WordSearch {
+    /// Empty words are ignored.
+    pub fn new<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            words: words
+                .into_iter()
+                .map(|w| w.as_ref().chars().collect())
+                .collect(),
+            dirs: Offset::DIRS_8.to_vec(),
+            wrap: false,
+        }
+    }
+
+    pub fn with_dirs(self, dirs: impl IntoIterator<Item = Offset>) -> Self {
+        Self {
+            dirs: dirs.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Lets words run off one edge of the grid and back in on the
+    /// opposite one.
+    pub fn with_wrap(self, wrap: bool) -> Self {
+        Self { wrap, ..self }
+    }
+
+    /// Every match, ordered by start position, then direction, then word.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for start in grid.positions() {
+            for &dir in &self.dirs {
+                for (word, letters) in self.words.iter().enumerate() {
+                    if let Some(cells) = self.spell(grid, start, dir, letters)
+                    {
+                        matches.push(Match {
+                            word,
+                            start,
+                            dir,
+                            cells,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    pub fn count(&self, grid: &Grid<char>) -> usize {
+        self.find(grid).len()
+    }
+
+    /// Cells spelling `letters` from `start` towards `dir`, if they do.
+    fn spell(
+        &self,
+        grid: &Grid<char>,
+        start: Pos,
+        dir: Offset,
+        letters: &[char],
+    ) -> Option<Vec<Pos>> {
+        if letters.is_empty() {
+            return None;
+        }
+        let mut cells: Vec<Pos> = Vec::with_capacity(letters.len());
+        let mut pos = start;
+        for (i, letter) in letters.iter().enumerate() {
+            if i > 0 {
+                pos = if self.wrap {
+                    grid.wrap(pos, dir)
+                } else {
+                    grid.step(pos, dir)?
+                };
+                if cells.contains(&pos) {
+                    return None;
+                }
+            }
+            if grid[pos] != *letter {
+                return None;
+            }
+            cells.push(pos);
+        }
+        Some(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::tests::grid;
+
+    fn cells(matches: &[Match]) -> impl Iterator<Item = Pos> + '_ {
+        matches.iter().flat_map(|m| m.cells.iter().copied())
+    }
+
+    #[test]
+    fn find() {
+        let g = grid("..X...\n.SAMX.\n.A..A.\nXMAS.S\n.X....");
+        let matches = WordSearch::new(["XMAS"]).find(&g);
+        let found: Vec<(Pos, Offset)> =
+            matches.iter().map(|m| (m.start, m.dir)).collect();
+        let expected = [
+            (Pos { r: 0, k: 2 }, Offset::SE),
+            (Pos { r: 1, k: 4 }, Offset::W),
+            (Pos { r: 3, k: 0 }, Offset::E),
+            (Pos { r: 4, k: 1 }, Offset::N),
+        ];
+        assert_eq!(&expected[..], found);
+        assert_eq!(g.to_string(), g.highlight(cells(&matches)));
+        let across = WordSearch::new(["XMAS", "SAM"])
+            .with_dirs([Offset::E, Offset::W]);
+        let found: Vec<usize> =
+            across.find(&g).iter().map(|m| m.word).collect();
+        assert_eq!(vec![1, 0, 0, 1], found);
+    }
+
+    #[test]
+    fn wrap() {
+        let g = grid("SXMA\n....");
+        let search = WordSearch::new(["XMAS"]).with_dirs([Offset::E]);
+        assert_eq!(0, search.count(&g));
+        let matches = search.with_wrap(true).find(&g);
+        assert_eq!(1, matches.len());
+        assert_eq!(Pos { r: 0, k: 1 }, matches[0].start);
+        assert_eq!("SXMA\n....\n", g.highlight(cells(&matches)));
+        // Looping around onto the same cells is not a match:
+        let g = grid("AB");
+        let search = WordSearch::new(["ABA", "AB", ""])
+            .with_dirs([Offset::E])
+            .with_wrap(true);
+        assert_eq!(1, search.count(&g));
+    }
+}
@@ -1,3 +1,31 @@
+use std::{
+    fmt,
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::bail;
+
+use crate::error::ParseError;
+
+macro_rules! day_entry {
+    ($module:ident) => {
+        DayEntry {
+            day: <$module::Data as Solution>::DAY,
+            name: stringify!($module),
+            solve: solve_each::<$module::Data>,
+            time: bench::time::<$module::Data>,
+        }
+    };
+}
+
+pub mod bench;
+pub mod error;
+pub mod graph;
+pub mod grid;
+
 pub mod dies_01;
 pub mod dies_02;
 pub mod dies_03;
@@ -5,3 +33,139 @@ pub mod dies_04;
 pub mod dies_05;
 pub mod dies_06;
 pub mod dies_07;
+// `make dies_NN` inserts new day modules above this line.
+
+/// Every implemented day, in order.
+pub static DAYS: &[DayEntry] = &[
+    day_entry!(dies_01),
+    day_entry!(dies_02),
+    day_entry!(dies_03),
+    day_entry!(dies_04),
+    day_entry!(dies_05),
+    day_entry!(dies_06),
+    day_entry!(dies_07),
+    // `make dies_NN` inserts new day entries above this line.
+];
+
+/// Looks up a day in [`DAYS`].
+pub fn day(n: u8) -> Option<&'static DayEntry> {
+    DAYS.iter().find(|entry| entry.day == n)
+}
+
+/// Common interface of every day's puzzle: parse the input once, then
+/// answer either part from the parsed data.
+pub trait Solution: Sized {
+    const DAY: u8;
+
+    type Answer1: fmt::Display;
+    type Answer2: fmt::Display;
+
+    fn parse(input: &str) -> Result<Self, ParseError>;
+
+    fn part1(&self) -> anyhow::Result<Self::Answer1>;
+
+    fn part2(&self) -> anyhow::Result<Self::Answer2>;
+
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let input = read_input_file(path)?;
+        let data = Self::parse(&input).map_err(|e| e.in_file(path))?;
+        Ok(data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl FromStr for Part {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::One),
+            "2" => Ok(Self::Two),
+            _ => bail!("Invalid part: {s:?}. Expected 1 or 2."),
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::One => write!(f, "1"),
+            Self::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Parses the input of any day and renders the answer of the given part.
+pub fn solve<S: Solution>(input: &str, part: Part) -> anyhow::Result<String> {
+    let data = S::parse(input)?;
+    let answer = match part {
+        Part::One => data.part1()?.to_string(),
+        Part::Two => data.part2()?.to_string(),
+    };
+    Ok(answer)
+}
+
+/// Receives each answer of [`solve_each`] with its part.
+pub type OnAnswer<'a> = dyn FnMut(Part, String) + 'a;
+
+/// Parses the input of any day once, then renders the answer of each given
+/// part in turn, handing it to `on_answer`, until one fails.
+pub fn solve_each<S: Solution>(
+    input: &str,
+    parts: &[Part],
+    on_answer: &mut OnAnswer,
+) -> anyhow::Result<()> {
+    let data = S::parse(input)?;
+    for &part in parts {
+        let answer = match part {
+            Part::One => data.part1()?.to_string(),
+            Part::Two => data.part2()?.to_string(),
+        };
+        on_answer(part, answer);
+    }
+    Ok(())
+}
+
+/// Reads a whole input file, failing with its path.
+pub fn read_input_file(path: &Path) -> anyhow::Result<String> {
+    std::fs::read_to_string(path).map_err(|e| input_file_error(path, e))
+}
+
+/// Opens an input file to read bit by bit, failing with its path.
+pub fn open_input_file(path: &Path) -> anyhow::Result<File> {
+    File::open(path).map_err(|e| input_file_error(path, e))
+}
+
+fn input_file_error(path: &Path, e: io::Error) -> anyhow::Error {
+    anyhow::anyhow!("Failure to open input file {:?}: {:?}", path, e)
+}
+
+/// A type-erased handle on a day's [`Solution`].
+pub struct DayEntry {
+    pub day: u8,
+    pub name: &'static str,
+    pub solve: fn(&str, &[Part], &mut OnAnswer) -> anyhow::Result<()>,
+    pub time: fn(&str) -> anyhow::Result<bench::Timings>,
+}
+
+impl DayEntry {
+    pub fn input_path(&self) -> PathBuf {
+        self.path("input")
+    }
+
+    pub fn example_path(&self) -> PathBuf {
+        self.path("example")
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        Path::new("tests/input")
+            .join(self.name)
+            .join(name)
+            .with_extension("txt")
+    }
+}
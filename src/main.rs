@@ -0,0 +1,432 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context};
+
+use adventus_codicis_mmxxiv::{
+    bench, dies_01, dies_03, dies_05, dies_06, error::ParseError, grid::Pos,
+    open_input_file, read_input_file, DayEntry, Part, Solution, DAYS,
+};
+
+const USAGE: &str = "\
+Usage:
+    acm list
+    acm run DAY [--part 1|2] [--input PATH|-] [--stream]
+    acm bench [DAY] [--runs N] [--save PATH] [--compare PATH]
+              [--threshold PERCENT]
+    acm patrol [--input PATH|-] [--obstruction ROW,COL]
+               [--frames DIR | --animate [--delay MS]]
+    acm audit [--input PATH|-] [--all] [--drop-rules] [--stable]
+
+    Without --part, both parts are solved.
+    Without --input, tests/input/dies_DD/input.txt is read.
+    --input - reads from stdin.
+    --stream reads day 1's lists line by line, sorting them on disk, for
+    lists too long to hold in memory, and runs day 3's program as it is
+    read. Day 3 reads its input once per part, so needs a --part to read
+    from stdin.
+
+    bench times parse, part 1 and part 2 of every day (or just DAY) on its
+    input.txt, keeping the best of --runs (default 1). --save writes the
+    timings as a JSON baseline, --compare reports the change against one
+    and fails if any phase got slower by more than --threshold (default
+    10) percent.
+
+    patrol draws day 6's guard path, with an optional extra obstruction at
+    the 0-based ROW,COL, and reports whether the guard exits or loops.
+    --frames writes every step's map to its own file in DIR, --animate
+    plays them in the terminal, --delay (default 50) milliseconds apart.
+
+    audit explains each of day 5's updates which is out of order: the rules
+    it breaks, with the 0-based positions of their pages, and the fewest
+    moves of single pages putting it in order, or why there are none.
+    --all also lists updates in order. --drop-rules ignores rules to break
+    cycles, --stable keeps pages no rule orders where they were, instead
+    of leaving such updates uncorrected.";
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|arg| arg.as_str()) {
+        Some("list") => {
+            list();
+            Ok(())
+        }
+        Some("run") => run(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("patrol") => patrol(&args[1..]),
+        Some("audit") => audit(&args[1..]),
+        Some("-h" | "--help" | "help") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(cmd) => bail!("Unknown command: {cmd:?}\n\n{USAGE}"),
+        None => bail!("Missing command.\n\n{USAGE}"),
+    }
+}
+
+fn list() {
+    for entry in DAYS {
+        println!("{:>2} {}", entry.day, entry.name);
+    }
+}
+
+fn run(args: &[String]) -> anyhow::Result<()> {
+    let mut day: Option<u8> = None;
+    let mut part: Option<Part> = None;
+    let mut input: Option<String> = None;
+    let mut stream = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stream" => {
+                stream = true;
+            }
+            "--part" | "-p" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing part."))?;
+                part = Some(val.parse()?);
+            }
+            "--input" | "-i" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing input."))?;
+                input = Some(val.to_string());
+            }
+            _ if day.is_none() => {
+                day = Some(
+                    arg.parse()
+                        .with_context(|| format!("Invalid day: {arg:?}"))?,
+                );
+            }
+            _ => bail!("Unexpected argument: {arg:?}\n\n{USAGE}"),
+        }
+    }
+    let day = day.ok_or_else(|| anyhow!("Missing day.\n\n{USAGE}"))?;
+    let entry = adventus_codicis_mmxxiv::day(day)
+        .ok_or_else(|| anyhow!("Day not implemented: {day}"))?;
+    if stream {
+        return run_streaming(input.as_deref(), entry, part);
+    }
+    let (path, input) = read_input(input.as_deref(), entry)?;
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+    // Answers are printed as they come, before any later part fails:
+    (entry.solve)(&input, &parts, &mut |_, answer| println!("{answer}"))
+        .map_err(|e| locate(e, path.as_deref()))
+}
+
+/// Solves day 1 or 3 straight from its input, see
+/// [`dies_01::Streaming`] and [`dies_03::Data::run_reader`].
+fn run_streaming(
+    arg: Option<&str>,
+    entry: &DayEntry,
+    part: Option<Part>,
+) -> anyhow::Result<()> {
+    let path = input_path(arg, entry);
+    let open = || -> anyhow::Result<Box<dyn BufRead>> {
+        Ok(match &path {
+            None => Box::new(std::io::stdin().lock()),
+            Some(path) => Box::new(BufReader::new(open_input_file(path)?)),
+        })
+    };
+    let answers = match entry.day {
+        dies_01::Data::DAY => stream_day_1(open()?, part),
+        dies_03::Data::DAY => {
+            if path.is_none() && part.is_none() {
+                bail!("Day 3 cannot read stdin twice, give a --part.");
+            }
+            let parts = match part {
+                Some(part) => vec![part],
+                None => vec![Part::One, Part::Two],
+            };
+            parts
+                .into_iter()
+                .map(|part| stream_day_3(open()?, part))
+                .collect()
+        }
+        _ => bail!("Only days 1 and 3 can be streamed."),
+    }
+    .map_err(|e| locate(e, path.as_deref()))?;
+    for answer in answers {
+        println!("{answer}");
+    }
+    Ok(())
+}
+
+fn stream_day_1(
+    reader: impl BufRead,
+    part: Option<Part>,
+) -> anyhow::Result<Vec<String>> {
+    let streaming = dies_01::Streaming::new();
+    match part {
+        Some(Part::One) => {
+            streaming.distance(reader).map(|d| vec![d.to_string()])
+        }
+        Some(Part::Two) => {
+            streaming.similarity(reader).map(|s| vec![s.to_string()])
+        }
+        None => streaming
+            .both(reader)
+            .map(|(d, s)| vec![d.to_string(), s.to_string()]),
+    }
+}
+
+fn stream_day_3(reader: impl BufRead, part: Part) -> anyhow::Result<String> {
+    let config = match part {
+        Part::One => dies_03::Config::PART_1,
+        Part::Two => dies_03::Config::PART_2,
+    };
+    let machine =
+        dies_03::Data::run_reader(reader, dies_03::Syntax::Classic, config)?;
+    Ok(machine.acc.to_string())
+}
+
+/// Path given by the `--input` argument: `None` for stdin, with `-`, or
+/// the day's default input file, without any.
+fn input_path(arg: Option<&str>, entry: &DayEntry) -> Option<PathBuf> {
+    match arg {
+        Some("-") => None,
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(entry.input_path()),
+    }
+}
+
+/// Reads the `--input` argument: a path, `-` for stdin, or nothing for
+/// the day's default input file.
+fn read_input(
+    arg: Option<&str>,
+    entry: &DayEntry,
+) -> anyhow::Result<(Option<PathBuf>, String)> {
+    let path = input_path(arg, entry);
+    let input = match &path {
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            buf
+        }
+        Some(path) => read_input_file(path)?,
+    };
+    Ok((path, input))
+}
+
+/// Attributes a parse error to the file it came from.
+fn locate(e: anyhow::Error, path: Option<&Path>) -> anyhow::Error {
+    match (e.downcast::<ParseError>(), path) {
+        (Ok(e), Some(path)) => e.in_file(path).into(),
+        (Ok(e), None) => e.into(),
+        (Err(e), _) => e,
+    }
+}
+
+fn bench(args: &[String]) -> anyhow::Result<()> {
+    let mut day: Option<u8> = None;
+    let mut runs: usize = 1;
+    let mut save: Option<PathBuf> = None;
+    let mut compare: Option<PathBuf> = None;
+    let mut threshold: f64 = 10.0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--runs" | "-n" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing runs."))?;
+                runs = val
+                    .parse()
+                    .with_context(|| format!("Invalid runs: {val:?}"))?;
+            }
+            "--save" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing path."))?;
+                save = Some(PathBuf::from(val));
+            }
+            "--compare" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing path."))?;
+                compare = Some(PathBuf::from(val));
+            }
+            "--threshold" => {
+                let val = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing threshold."))?;
+                threshold = val
+                    .parse()
+                    .with_context(|| format!("Invalid threshold: {val:?}"))?;
+            }
+            _ if day.is_none() => {
+                day = Some(
+                    arg.parse()
+                        .with_context(|| format!("Invalid day: {arg:?}"))?,
+                );
+            }
+            _ => bail!("Unexpected argument: {arg:?}\n\n{USAGE}"),
+        }
+    }
+    let entries: Vec<_> = match day {
+        Some(day) => vec![adventus_codicis_mmxxiv::day(day)
+            .ok_or_else(|| anyhow!("Day not implemented: {day}"))?],
+        None => DAYS.iter().collect(),
+    };
+    let before = compare.as_deref().map(bench::load).transpose()?;
+    let mut after = bench::Baseline::new();
+    let mut regressions = Vec::new();
+    println!("{}", bench::header());
+    for entry in entries {
+        let timings = bench::run(entry, runs.max(1))?;
+        let mut row = bench::row(entry.name, &timings);
+        if let Some(prev) = before.as_ref().and_then(|b| b.get(entry.name)) {
+            let delta = bench::Delta::new(prev, &timings);
+            row.push_str(&format!(" {delta}"));
+            if delta.is_regression(threshold / 100.0) {
+                row.push_str(" REGRESSION");
+                regressions.push(entry.name);
+            }
+        }
+        println!("{row}");
+        after.insert(entry.name.to_string(), timings);
+    }
+    if let Some(path) = save {
+        bench::save(&path, &after)?;
+    }
+    if !regressions.is_empty() {
+        bail!("Slower than baseline by over {threshold}%: {regressions:?}");
+    }
+    Ok(())
+}
+
+fn patrol(args: &[String]) -> anyhow::Result<()> {
+    let mut input: Option<String> = None;
+    let mut obstruction: Option<Pos> = None;
+    let mut frames: Option<PathBuf> = None;
+    let mut animate = false;
+    let mut delay: u64 = 50;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing input."))?;
+                input = Some(val.to_string());
+            }
+            "--obstruction" | "-o" => {
+                let val = args
+                    .next()
+                    .ok_or_else(|| anyhow!("Missing obstruction."))?;
+                let (r, k) = val
+                    .split_once(',')
+                    .ok_or_else(|| anyhow!("Invalid obstruction: {val:?}"))?;
+                let r = r
+                    .parse()
+                    .with_context(|| format!("Invalid row: {r:?}"))?;
+                let k = k
+                    .parse()
+                    .with_context(|| format!("Invalid column: {k:?}"))?;
+                obstruction = Some(Pos { r, k });
+            }
+            "--frames" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing path."))?;
+                frames = Some(PathBuf::from(val));
+            }
+            "--animate" => {
+                animate = true;
+            }
+            "--delay" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing delay."))?;
+                delay = val
+                    .parse()
+                    .with_context(|| format!("Invalid delay: {val:?}"))?;
+            }
+            _ => bail!("Unexpected argument: {arg:?}\n\n{USAGE}"),
+        }
+    }
+    let entry = adventus_codicis_mmxxiv::day(dies_06::Data::DAY).ok_or_else(
+        || anyhow!("Day not registered: {}", dies_06::Data::DAY),
+    )?;
+    let (path, input) = read_input(input.as_deref(), entry)?;
+    let data = dies_06::Data::parse(&input)
+        .map_err(|e| locate(e.into(), path.as_deref()))?;
+    let mut trace = data.trace(obstruction)?;
+    if let Some(dir) = frames {
+        let count = dies_06::write_frames(&dir, &mut trace)?;
+        println!("Wrote {count} frames to {dir:?}.");
+    } else if animate {
+        for frame in &mut trace {
+            // Clear the screen and move the cursor to the top-left:
+            print!("\x1b[2J\x1b[H{frame}");
+            std::thread::sleep(std::time::Duration::from_millis(delay));
+        }
+    } else {
+        trace.finish();
+        print!("{}", trace.render());
+    }
+    match trace.outcome() {
+        Some(dies_06::Outcome::Exited) => {
+            println!("Exited after {} steps.", trace.steps());
+        }
+        Some(dies_06::Outcome::Looped { start, dir, len }) => {
+            println!(
+                "Looped after {} steps: back at row {}, column {}, \
+                facing {dir:?}, every {len} steps.",
+                trace.steps(),
+                start.r,
+                start.k
+            );
+        }
+        None => unreachable!(),
+    }
+    Ok(())
+}
+
+fn audit(args: &[String]) -> anyhow::Result<()> {
+    let mut input: Option<String> = None;
+    let mut all = false;
+    let mut cycles = dies_05::Cycles::Fail;
+    let mut ambiguity = dies_05::Ambiguity::Fail;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" | "-i" => {
+                let val =
+                    args.next().ok_or_else(|| anyhow!("Missing input."))?;
+                input = Some(val.to_string());
+            }
+            "--all" => {
+                all = true;
+            }
+            "--drop-rules" => {
+                cycles = dies_05::Cycles::DropRules;
+            }
+            "--stable" => {
+                ambiguity = dies_05::Ambiguity::Stable;
+            }
+            _ => bail!("Unexpected argument: {arg:?}\n\n{USAGE}"),
+        }
+    }
+    let entry = adventus_codicis_mmxxiv::day(dies_05::Data::DAY).ok_or_else(
+        || anyhow!("Day not registered: {}", dies_05::Data::DAY),
+    )?;
+    let (path, input) = read_input(input.as_deref(), entry)?;
+    let data = dies_05::Data::parse(&input)
+        .map_err(|e| locate(e.into(), path.as_deref()))?
+        .with_cycles(cycles)
+        .with_ambiguity(ambiguity);
+    let mut invalid = 0;
+    for (i, update) in data.updates().iter().enumerate() {
+        let report = data.validate(update);
+        if !report.is_valid() {
+            invalid += 1;
+        }
+        if all || !report.is_valid() {
+            print!("Update {}: {report}", i + 1);
+        }
+    }
+    println!(
+        "{invalid} of {} updates out of order.",
+        data.updates().len()
+    );
+    Ok(())
+}
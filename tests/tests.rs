@@ -1,71 +1,142 @@
-macro_rules! path {
-    ($dies:expr, $type:expr) => {
-        std::path::Path::new(concat!(
-            "tests/input/dies",
-            stringify!($dies),
-            "/",
-            $type,
-            ".txt"
-        ))
-    };
+use std::path::{Path, PathBuf};
+
+use adventus_codicis_mmxxiv::{solve, Part, Solution};
+
+/// Checks the expected answers listed for the part in the day's
+/// `answers.toml` whose keys `keep` selects. Each key names an input file
+/// in the same directory: `<key>_<part>.txt` when the part has its own,
+/// otherwise `<key>.txt`.
+fn check<S: Solution>(dies: &str, part: Part, keep: fn(&str) -> bool) {
+    let dir = Path::new("tests/input").join(dies);
+    let answers: toml::Table =
+        std::fs::read_to_string(dir.join("answers.toml"))
+            .unwrap()
+            .parse()
+            .unwrap();
+    let section = format!("part{part}");
+    let expectations: Vec<_> = answers
+        .get(&section)
+        .and_then(|answers| answers.as_table())
+        .unwrap_or_else(|| panic!("Missing [{section}] in {dir:?}."))
+        .iter()
+        .filter(|(name, _)| keep(name))
+        .collect();
+    assert!(
+        !expectations.is_empty(),
+        "No answers in [{section}] of {dir:?}."
+    );
+    for (name, expected) in expectations {
+        let expected = match expected {
+            toml::Value::Integer(n) => n.to_string(),
+            toml::Value::String(s) => s.to_string(),
+            _ => panic!("Invalid answer for {name:?}: {expected:?}"),
+        };
+        let path = input_path(&dir, name, part);
+        let input = std::fs::read_to_string(&path).unwrap();
+        let actual = solve::<S>(&input, part).unwrap();
+        assert_eq!(expected, actual, "Part {part} of {path:?}.");
+    }
 }
 
-macro_rules! path_to_example {
-    ($dies:ident) => {
-        path!($dies, "example")
-    };
+/// Every key but `input` names an example.
+fn is_example(name: &str) -> bool {
+    name != "input"
 }
 
-macro_rules! path_to_input {
-    ($dies:ident) => {
-        path!($dies, "input")
-    };
+fn is_input(name: &str) -> bool {
+    name == "input"
+}
+
+fn input_path(dir: &Path, name: &str, part: Part) -> PathBuf {
+    let specific = dir.join(format!("{name}_{part}.txt"));
+    if specific.exists() {
+        specific
+    } else {
+        dir.join(format!("{name}.txt"))
+    }
 }
 
 macro_rules! dies {
-  ($n:ident, $p1_ex:expr, $p1_in:expr, $p2_ex:expr, $p2_in:expr) => {
-       paste::paste! {
-           #[cfg(test)]
-           mod [<dies $n>] {
-               mod part_1 {
-                   #[test]
-                   fn example() {
-                       let data = adventus_codicis_mmxxiv::[<dies $n>]::Data::load(path_to_example!($n)).unwrap();
-                       assert_eq!($p1_ex, data.solve1().unwrap());
-                   }
-
-                   #[test]
-                   fn input() {
-                       let data = adventus_codicis_mmxxiv::[<dies $n>]::Data::load(path_to_input!($n)).unwrap();
-                       assert_eq!($p1_in, data.solve1().unwrap());
-                   }
-               }
-               mod part_2 {
+    ($n:ident) => {
+        paste::paste! {
+            #[cfg(test)]
+            mod [<dies $n>] {
+                use adventus_codicis_mmxxiv::{[<dies $n>]::Data, Part};
+
+                use crate::{check, is_example, is_input};
+
+                const DIES: &str = stringify!([<dies $n>]);
+
+                mod part_1 {
+                    use super::*;
+
                     #[test]
                     fn example() {
-                        let data = adventus_codicis_mmxxiv::[<dies $n>]::Data::load(path_to_example!($n)).unwrap();
-                        assert_eq!($p2_ex, data.solve2().unwrap());
+                        check::<Data>(DIES, Part::One, is_example);
                     }
 
                     #[test]
                     fn input() {
-                        let data = adventus_codicis_mmxxiv::[<dies $n>]::Data::load(path_to_input!($n)).unwrap();
-                        assert_eq!($p2_in, data.solve2().unwrap());
+                        check::<Data>(DIES, Part::One, is_input);
                     }
-               }
-           }
-       }
-  };
+                }
+
+                mod part_2 {
+                    use super::*;
+
+                    #[test]
+                    fn example() {
+                        check::<Data>(DIES, Part::Two, is_example);
+                    }
+
+                    #[test]
+                    fn input() {
+                        check::<Data>(DIES, Part::Two, is_input);
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[test]
+fn registry() {
+    let days = adventus_codicis_mmxxiv::DAYS;
+    for (i, entry) in days.iter().enumerate() {
+        assert_eq!(i + 1, entry.day as usize, "Days in order, no gaps.");
+        assert_eq!(format!("dies_{:02}", entry.day), entry.name);
+        assert!(entry.input_path().exists());
+        assert!(entry.example_path().exists());
+    }
+}
+
+#[test]
+fn solve_each() {
+    let entry = adventus_codicis_mmxxiv::day(1).unwrap();
+    let input = std::fs::read_to_string(entry.example_path()).unwrap();
+    let mut answers = Vec::new();
+    let parts = [Part::Two, Part::One];
+    (entry.solve)(&input, &parts, &mut |part, answer| {
+        answers.push((part, answer))
+    })
+    .unwrap();
+    let expected: Vec<(Part, String)> = parts
+        .iter()
+        .map(|&part| {
+            (
+                part,
+                solve::<adventus_codicis_mmxxiv::dies_01::Data>(&input, part)
+                    .unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(expected, answers);
 }
 
-dies!(_01, 11, 1320851, 31, 26859182);
-dies!(_02, 2, 306, 4, 366);
-
-// XXX 161 is wrong for part 2, since example input should be different,
-//     but this macro doesn't account for it,
-//     so we handle it within the dies_03 module's inner test suite .
-dies!(_03, 161, 188116424, 161, 104245808);
-dies!(_04, 18, 2517, 9, 1960);
-dies!(_05, 143, 4662, 123, 5900);
-dies!(_06, 41, 5080, 6, 1919);
-dies!(_07, 3749, 20281182715321, 11387, 159490400628354);
+dies!(_01);
+dies!(_02);
+dies!(_03);
+dies!(_04);
+dies!(_05);
+dies!(_06);
+dies!(_07);